@@ -0,0 +1,81 @@
+struct Stroke {
+    center: vec2<f32>,
+    radius: f32,
+    value: f32,
+    shape: u32,   // 0 circle, 1 square, 2 gaussian
+    mode: u32,    // 0 add, 1 set, 2 erase
+    channel: u32,
+    _padding: u32,
+}
+
+@group(0) @binding(0)
+var state_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var<storage, read> strokes: array<Stroke>;
+
+#ifdef STATE_R32FLOAT
+@group(0) @binding(2)
+var texture: texture_storage_2d<r32float, write>;
+#else ifdef STATE_RGBA16FLOAT
+@group(0) @binding(2)
+var texture: texture_storage_2d<rgba16float, write>;
+#else
+@group(0) @binding(2)
+var texture: texture_storage_2d<rgba32float, write>;
+#endif
+
+
+// Share of the stroke applied to a cell at `offset` cells from its center.
+fn brush_weight(stroke: Stroke, offset: vec2<f32>) -> f32 {
+    switch stroke.shape {
+        case 0u: {
+            return select(0.0, 1.0, length(offset) <= stroke.radius);
+        }
+        case 1u: {
+            return select(0.0, 1.0, max(offset.x, offset.y) <= stroke.radius);
+        }
+        default: {
+            let distance = length(offset) / max(stroke.radius, 1.0);
+            return select(0.0, exp(-4.5 * distance * distance), distance <= 1.0);
+        }
+    }
+}
+
+// Copies the state with the frame's strokes applied in order.
+@compute @workgroup_size(8, 8, 1)
+fn brush(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let location = vec2<i32>(invocation_id.xy);
+    let dimensions = vec2<i32>(textureDimensions(texture));
+    if any(location >= dimensions) {
+        return;
+    }
+
+    var cell = textureLoad(state_texture, location, 0);
+    for (var i = 0u; i < arrayLength(&strokes); i++) {
+        let stroke = strokes[i];
+        // `collect_strokes` drops these already, a state cell holds at most four channels
+        if stroke.channel > 3u {
+            continue;
+        }
+        // the world wraps around, so does the brush
+        let distance = abs(vec2<f32>(location) + 0.5 - stroke.center);
+        let offset = min(distance, vec2<f32>(dimensions) - distance);
+        let weight = brush_weight(stroke, offset);
+
+        let value = cell[stroke.channel];
+        switch stroke.mode {
+            case 0u: {
+                cell[stroke.channel] = clamp(value + stroke.value * weight, 0.0, 1.0);
+            }
+            case 1u: {
+                cell[stroke.channel] = mix(value, stroke.value, weight);
+            }
+            default: {
+                cell[stroke.channel] = value * (1.0 - weight);
+            }
+        }
+    }
+
+    textureStore(texture, location, cell);
+}
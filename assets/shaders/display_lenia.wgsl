@@ -0,0 +1,96 @@
+@group(0) @binding(0)
+var state_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var display_texture: texture_storage_2d<rgba8unorm, write>;
+
+struct LeniaGPUParams {
+    random_float: f32,
+    kernel_resolution: f32,
+    delta_time: f32,
+    dt: f32,
+    growth_resolution: u32,
+    channels: u32,
+    kernel_count: u32,
+    levels: u32,
+    noise_octaves: u32,
+    noise_scale: f32,
+    noise_detail: f32,
+    noise_offset_x: f32,
+    noise_offset_y: f32,
+}
+
+@group(0) @binding(2)
+var <uniform> params: LeniaGPUParams;
+
+// Colormap lookup table for single channel worlds.
+@group(0) @binding(3)
+var <storage, read> colormap: array<vec4<f32>>;
+
+struct LeniaGPUDisplay {
+    channel_colors: array<vec4<f32>, 3>,
+    lut_size: u32,
+    field: u32,
+    side_by_side: u32,
+}
+
+@group(0) @binding(4)
+var <uniform> display_params: LeniaGPUDisplay;
+
+// Potential, growth and delta written by the last update.
+@group(0) @binding(5)
+var fields_texture: texture_2d_array<f32>;
+
+// Field of the cell at `location` mapped to [0, 1], signed fields around 0.5.
+fn field_value(location: vec2<i32>, field: u32) -> vec4<f32> {
+    switch field {
+        case 0u: {
+            return clamp(textureLoad(state_texture, location, 0), vec4<f32>(0.0), vec4<f32>(1.0));
+        }
+        case 1u: {
+            return clamp(textureLoad(fields_texture, location, 0, 0), vec4<f32>(0.0), vec4<f32>(1.0));
+        }
+        case 2u: {
+            let growth = textureLoad(fields_texture, location, 1, 0);
+            return clamp(0.5 + 0.5 * growth, vec4<f32>(0.0), vec4<f32>(1.0));
+        }
+        default: {
+            // the change of a step is at most `dt` in either direction
+            let delta = textureLoad(fields_texture, location, 2, 0) / params.dt;
+            return clamp(0.5 + 0.5 * delta, vec4<f32>(0.0), vec4<f32>(1.0));
+        }
+    }
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn display(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let location = vec2<i32>(invocation_id.xy);
+    let dimensions = vec2<i32>(textureDimensions(display_texture));
+    if any(location >= dimensions) {
+        return;
+    }
+
+    // Side by side, every quadrant shows one field of the whole world at half resolution.
+    var field = display_params.field;
+    var source = location;
+    if display_params.side_by_side == 1u {
+        let half = max(dimensions / 2, vec2<i32>(1));
+        let tile = min(location / half, vec2<i32>(1));
+        field = u32(tile.x + 2 * tile.y);
+        source = min((location - tile * half) * 2, dimensions - 1);
+    }
+    let state = field_value(source, field);
+
+    // A single channel goes through the colormap, otherwise the channel colors are mixed.
+    var color = vec3<f32>(0.0);
+    if params.channels == 1u {
+        let index = u32(round(state.x * f32(display_params.lut_size - 1u)));
+        color = colormap[index].rgb;
+    } else {
+        for (var channel = 0u; channel < min(params.channels, 3u); channel++) {
+            color += state[channel] * display_params.channel_colors[channel].rgb;
+        }
+    }
+
+    textureStore(display_texture, location, vec4<f32>(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0));
+}
@@ -1,13 +1,28 @@
-@group(0) @binding(0)
-var texture: texture_storage_2d<rgba8unorm, read_write>;
+#ifdef STATE_R32FLOAT
+@group(0) @binding(5)
+var texture: texture_storage_2d<r32float, write>;
+#else ifdef STATE_RGBA16FLOAT
+@group(0) @binding(5)
+var texture: texture_storage_2d<rgba16float, write>;
+#else
+@group(0) @binding(5)
+var texture: texture_storage_2d<rgba32float, write>;
+#endif
 
 struct LeniaGPUParams {
     random_float: f32,
-    kernel_area: f32,
     kernel_resolution: f32,
     delta_time: f32,
     dt: f32,
     growth_resolution: u32,
+    channels: u32,
+    kernel_count: u32,
+    levels: u32,
+    noise_octaves: u32,
+    noise_scale: f32,
+    noise_detail: f32,
+    noise_offset_x: f32,
+    noise_offset_y: f32,
 }
 
 
@@ -15,7 +30,7 @@ struct LeniaGPUParams {
 var <uniform> params: LeniaGPUParams;
 
 @group(0) @binding(2)
-var kernel_texture: texture_storage_2d<rgba32float, read>;
+var kernel_texture: texture_storage_2d_array<rgba32float, read>;
 
 @group(0) @binding(3)
 var <storage, read> growth_array: array<f32>;
@@ -97,19 +112,48 @@ fn perlinNoise3(P: vec3<f32>) -> f32 {
   return 2.2 * n_xyz;
 }
 
+// Fractional brownian motion, every octave half the size and weight of the previous one, or
+// the product of a large and a detailed layer when `noise_detail` is set.
+fn noise(invocation_id: vec3<u32>, channel: u32) -> f32 {
+    // The seed picks the region of the noise volume, every channel samples a different slice.
+    let offset = vec3<f32>(params.noise_offset_x, params.noise_offset_y, params.random_float + f32(channel) * 10.0);
+    let position = vec2<f32>(invocation_id.xy);
+
+    if params.noise_detail > 0.0 {
+        return perlinNoise3(vec3<f32>(position / params.noise_scale, 0.0) + offset)
+            * perlinNoise3(vec3<f32>(position / params.noise_detail, 0.0) + offset);
+    }
+
+    var value = 0.0;
+    var total = 0.0;
+    var amplitude = 1.0;
+    var zoom = params.noise_scale;
+    for (var octave = 0u; octave < params.noise_octaves; octave++) {
+        value += amplitude * perlinNoise3(vec3<f32>(position / zoom, 0.0) + offset);
+        total += amplitude;
+        amplitude *= 0.5;
+        zoom *= 0.5;
+    }
+
+    return value / max(total, 1.0);
+}
+
 @compute @workgroup_size(8, 8, 1)
 fn init(@builtin(global_invocation_id) invocation_id: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
     let location = vec2<i32>(invocation_id.xy);
-
-    // big landscape
-    let zoom1 = 100.0;
-    let value = perlinNoise3(vec3<f32>(f32(invocation_id.x)/zoom1, f32(invocation_id.y)/zoom1, params.random_float));
-
-    // detailed landscape
-    let zoom2 = 40.0;
-    let small_value = perlinNoise3(vec3<f32>(f32(invocation_id.x)/zoom2, f32(invocation_id.y)/zoom2, params.random_float));
-    
-    let color = vec4<f32>(value * small_value);
+    if any(location >= vec2<i32>(textureDimensions(texture))) {
+        return;
+    }
+
+    var color = vec4<f32>(noise(invocation_id, 0u), 0.0, 0.0, 1.0);
+    if params.channels > 1u {
+        color.y = noise(invocation_id, 1u);
+        color.z = select(0.0, noise(invocation_id, 2u), params.channels > 2u);
+    }
+    color = clamp(color, vec4<f32>(0.0), vec4<f32>(1.0));
+    if params.levels > 0u {
+        color = round(color * f32(params.levels)) / f32(params.levels);
+    }
 
     textureStore(texture, location, color);
 }
\ No newline at end of file
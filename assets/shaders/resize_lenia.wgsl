@@ -0,0 +1,57 @@
+@group(0) @binding(0)
+var previous_texture: texture_2d<f32>;
+
+#ifdef STATE_R32FLOAT
+@group(0) @binding(1)
+var texture: texture_storage_2d<r32float, write>;
+#else ifdef STATE_RGBA16FLOAT
+@group(0) @binding(1)
+var texture: texture_storage_2d<rgba16float, write>;
+#else
+@group(0) @binding(1)
+var texture: texture_storage_2d<rgba32float, write>;
+#endif
+
+
+// Keeps the centered overlap of the previous and new worlds, new area starts empty.
+@compute @workgroup_size(8, 8, 1)
+fn crop(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let location = vec2<i32>(invocation_id.xy);
+    let dimensions = vec2<i32>(textureDimensions(texture));
+    if any(location >= dimensions) {
+        return;
+    }
+
+    let previous_dimensions = vec2<i32>(textureDimensions(previous_texture));
+    let source = location + (previous_dimensions - dimensions) / 2;
+
+    var color = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    if all(source >= vec2<i32>(0)) && all(source < previous_dimensions) {
+        color = textureLoad(previous_texture, source, 0);
+    }
+
+    textureStore(texture, location, color);
+}
+
+// Bilinear interpolation of the previous world stretched over the new one.
+@compute @workgroup_size(8, 8, 1)
+fn resample(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let location = vec2<i32>(invocation_id.xy);
+    let dimensions = vec2<i32>(textureDimensions(texture));
+    if any(location >= dimensions) {
+        return;
+    }
+
+    let previous_dimensions = vec2<i32>(textureDimensions(previous_texture));
+    let scale = vec2<f32>(previous_dimensions) / vec2<f32>(dimensions);
+    let position = (vec2<f32>(location) + 0.5) * scale - 0.5;
+    let base = floor(position);
+    let t = position - base;
+
+    let low = clamp(vec2<i32>(base), vec2<i32>(0), previous_dimensions - 1);
+    let high = clamp(vec2<i32>(base) + 1, vec2<i32>(0), previous_dimensions - 1);
+    let top = mix(textureLoad(previous_texture, low, 0), textureLoad(previous_texture, vec2<i32>(high.x, low.y), 0), t.x);
+    let bottom = mix(textureLoad(previous_texture, vec2<i32>(low.x, high.y), 0), textureLoad(previous_texture, high, 0), t.x);
+
+    textureStore(texture, location, mix(top, bottom, t.y));
+}
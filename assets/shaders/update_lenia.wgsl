@@ -1,13 +1,28 @@
+// The previous generation, every step writes the next one to `texture`.
 @group(0) @binding(0)
-var texture: texture_storage_2d<rgba8unorm, read_write>;
+var previous_texture: texture_2d<f32>;
 
 struct LeniaGPUParams {
     random_float: f32,
-    kernel_area: f32,
     kernel_resolution: f32,
     delta_time: f32,
     dt: f32,
     growth_resolution: u32,
+    channels: u32,
+    kernel_count: u32,
+    levels: u32,
+    noise_octaves: u32,
+    noise_scale: f32,
+    noise_detail: f32,
+    noise_offset_x: f32,
+    noise_offset_y: f32,
+}
+
+struct LeniaGPUKernel {
+    source_channel: u32,
+    target_channel: u32,
+    weight: f32,
+    area: f32,
 }
 
 
@@ -15,23 +30,43 @@ struct LeniaGPUParams {
 var <uniform> params: LeniaGPUParams;
 
 @group(0) @binding(2)
-var kernel_texture: texture_storage_2d<rgba32float, read>;
+var kernel_texture: texture_storage_2d_array<rgba32float, read>;
 
 @group(0) @binding(3)
 var <storage, read> growth_array: array<f32>;
 
+@group(0) @binding(4)
+var <storage, read> kernels: array<LeniaGPUKernel>;
+
+// The state texture format is selected by the `StateFormat` shader def.
+#ifdef STATE_R32FLOAT
+@group(0) @binding(5)
+var texture: texture_storage_2d<r32float, write>;
+#else ifdef STATE_RGBA16FLOAT
+@group(0) @binding(5)
+var texture: texture_storage_2d<rgba16float, write>;
+#else
+@group(0) @binding(5)
+var texture: texture_storage_2d<rgba32float, write>;
+#endif
+
+// Potential, growth and delta of every channel, in this layer order. Only written by the
+// pipeline built with `WRITE_FIELDS`.
+@group(0) @binding(6)
+var fields_texture: texture_storage_2d_array<rgba32float, write>;
+
 
 fn wrap(coords: vec2<i32>) -> vec2<i32> {
-    let dimensions: vec2<i32> = textureDimensions(texture);
+    let dimensions: vec2<i32> = textureDimensions(previous_texture);
     return vec2<i32>(fract(vec2<f32>(coords) / vec2<f32>(dimensions)) * vec2<f32>(dimensions));
 }
 
-fn calculate_with_texture(location: vec2<i32>, the_texture: texture_storage_2d<rgba32float, read>, the_area: vec4<f32>, radius: f32) -> vec4<f32> {
-    var sum: vec4<f32> = vec4(0.0);
+fn calculate_with_texture(location: vec2<i32>, kernel_index: u32, source: u32, the_area: f32, radius: f32) -> f32 {
+    var sum: f32 = 0.0;
     for (var dx: f32 = -radius; dx <= radius; dx += 1.0) {
         for (var dy: f32 = -radius; dy <= radius; dy += 1.0) {
-            let weight = textureLoad(the_texture, wrap(vec2<i32>(i32(radius)) + vec2<i32>(i32(dx), i32(dy))));
-            let value = textureLoad(texture, wrap(location + vec2<i32>(i32(dx), i32(dy))));
+            let weight = textureLoad(kernel_texture, wrap(vec2<i32>(i32(radius)) + vec2<i32>(i32(dx), i32(dy))), i32(kernel_index)).x;
+            let value = textureLoad(previous_texture, wrap(location + vec2<i32>(i32(dx), i32(dy))), 0)[source];
             sum += value * weight;
         }
     }
@@ -39,32 +74,57 @@ fn calculate_with_texture(location: vec2<i32>, the_texture: texture_storage_2d<r
     return sum / the_area;
 }
 
-fn calculate_growth(value: f32, resolution: u32) -> f32 {
+// Each kernel has its own lookup table of `resolution` entries in the growth array.
+fn calculate_growth(value: f32, kernel_index: u32, resolution: u32) -> f32 {
+    let offset = kernel_index * resolution;
     let float_index = value * (f32(resolution) - 1.0);
     if float_index == 0.0 {
-        return growth_array[0];
+        return growth_array[offset];
     }
     let left_index = floor(float_index);
     let right_index = ceil(float_index);
     let left_weight = fract(float_index);
     let right_weight = 1.0 - fract(float_index);
     
-    return (growth_array[u32(left_index)] * left_weight + growth_array[u32(right_index)] * right_weight);
+    return (growth_array[offset + u32(left_index)] * left_weight + growth_array[offset + u32(right_index)] * right_weight);
 }
 
 @compute @workgroup_size(8, 8, 1)
 fn update(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
     let location = vec2<i32>(i32(invocation_id.x), i32(invocation_id.y));
+    // The last workgroups overhang worlds that are not a multiple of the workgroup size.
+    if any(location >= vec2<i32>(textureDimensions(texture))) {
+        return;
+    }
 
-
-    let current = clamp(textureLoad(texture, location).x, 0.0, 1.0);
-    let potential = calculate_with_texture(location, kernel_texture, vec4<f32>(params.kernel_area), (params.kernel_resolution - 1.0)/2.0);
-    let growth = 2.0 * calculate_growth(potential.x, params.growth_resolution) - 1.0;
+    let current = clamp(textureLoad(previous_texture, location, 0), vec4<f32>(0.0), vec4<f32>(1.0));
+    let radius = (params.kernel_resolution - 1.0) / 2.0;
+
+    // Growth of every kernel scaled by its weight and summed per target channel, Σ h_i G_i.
+    var potentials = vec4<f32>(0.0);
+    var growth = vec4<f32>(0.0);
+    var weights = vec4<f32>(0.0);
+    for (var i: u32 = 0u; i < params.kernel_count; i += 1u) {
+        let kernel = kernels[i];
+        let potential = calculate_with_texture(location, i, kernel.source_channel, kernel.area, radius);
+        potentials[kernel.target_channel] += kernel.weight * potential;
+        growth[kernel.target_channel] += kernel.weight * (2.0 * calculate_growth(potential, i, params.growth_resolution) - 1.0);
+        weights[kernel.target_channel] += kernel.weight;
+    }
     let timestep = params.dt;
 
-    let color = vec4<f32>(clamp(current + timestep * growth, 0.0, 1.0));
+    var next = clamp(current + timestep * growth, vec4<f32>(0.0), vec4<f32>(1.0));
+    // Discrete-state Lenia rounds every channel to `levels` steps.
+    if params.levels > 0u {
+        next = round(next * f32(params.levels)) / f32(params.levels);
+    }
 
-    storageBarrier();
+    textureStore(texture, location, vec4<f32>(next.xyz, 1.0));
 
-    textureStore(texture, location, color);
+#ifdef WRITE_FIELDS
+    let average_potential = select(vec4<f32>(0.0), potentials / weights, weights != vec4<f32>(0.0));
+    textureStore(fields_texture, location, 0, vec4<f32>(average_potential.xyz, 1.0));
+    textureStore(fields_texture, location, 1, vec4<f32>(growth.xyz, 1.0));
+    textureStore(fields_texture, location, 2, vec4<f32>((next - current).xyz, 1.0));
+#endif
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lenia::*;
+
+const SIZE: (u32, u32) = (256, 256);
+
+// Kernel shells and radii used by the `game_of_life`, `smoothlife` and `lenia_game` examples.
+fn example_boards() -> Vec<(&'static str, LeniaBoard)> {
+    let gol_kernel_core = Mapping::new(Arc::new(|x| {
+        if x < 0.25 {
+            0.5
+        } else if x <= 0.75 {
+            1.0
+        } else {
+            0.0
+        }
+    }));
+    let growth = || {
+        Mapping::from_type(MappingType::GaussianGrowth {
+            mu: 0.25,
+            sigma: 0.03,
+        })
+    };
+
+    vec![
+        (
+            "game_of_life",
+            LeniaBoard::new(
+                LeniaRule::new(KernelShell::new(vec![1.0], gol_kernel_core), growth()),
+                SIZE,
+                2,
+                1.0,
+                100,
+            ),
+        ),
+        (
+            "smoothlife",
+            LeniaBoard::new(
+                LeniaRule::new(
+                    KernelShell::new(vec![1.0], Mapping::from_type(MappingType::StepCore)),
+                    growth(),
+                ),
+                SIZE,
+                13,
+                1.0,
+                100,
+            ),
+        ),
+        (
+            "lenia_game",
+            LeniaBoard::new(
+                LeniaRule::new(
+                    KernelShell::new(
+                        vec![0.5, 2.0 / 3.0, 1.0],
+                        Mapping::from_type(MappingType::GaussianCore { alpha: 4.0 }),
+                    ),
+                    growth(),
+                ),
+                SIZE,
+                26,
+                0.1,
+                100,
+            ),
+        ),
+    ]
+}
+
+fn convolution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convolution");
+    group.sample_size(10);
+
+    for (name, board) in example_boards() {
+        let cells: Vec<f32> = (0..SIZE.0 * SIZE.1).map(|_| rand::random()).collect();
+
+        for mode in [ConvolutionMode::Direct, ConvolutionMode::Fft] {
+            let mut cpu_lenia = CpuLenia::new(&board).with_convolution_mode(mode);
+            cpu_lenia.set_cells(0, cells.clone());
+
+            group.bench_function(BenchmarkId::new(format!("{mode:?}"), name), |b| {
+                b.iter(|| cpu_lenia.potential_field(0))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, convolution);
+criterion_main!(benches);
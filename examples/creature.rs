@@ -0,0 +1,33 @@
+use lenia::*;
+
+const SIZE: (u32, u32) = (1280, 720);
+
+// cargo run --example creature -- "Orbium unicaudatus"
+fn main() {
+    let library = CreatureLibrary::load("assets/creatures/animals.json").unwrap();
+
+    let query = std::env::args().nth(1).unwrap_or_else(|| "O2u".to_string());
+    let creature = library
+        .get_by_code(&query)
+        .or_else(|| library.get_by_name(&query))
+        .or_else(|| library.search(&query).next())
+        .unwrap_or_else(|| panic!("no creature matches `{query}`"));
+    println!("{} ({}): {}", creature.name, creature.code, creature.params);
+
+    let lenia_board = creature.to_board(SIZE, 100).unwrap();
+
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                // uncomment for unthrottled FPS
+                // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .run();
+}
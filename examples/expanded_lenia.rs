@@ -0,0 +1,48 @@
+use lenia::*;
+
+const SIZE: (u32, u32) = (1280, 720);
+
+fn main() -> Result<(), LeniaError> {
+    let kernel_core = || Mapping::from_type(MappingType::GaussianCore { alpha: 4.0 });
+    let growth = |mu, sigma| Mapping::from_type(MappingType::GaussianGrowth { mu, sigma });
+
+    // One channel, three kernels of different sizes whose growths are summed by weight.
+    let kernels = vec![
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0], kernel_core()),
+            growth(0.15, 0.015),
+        )
+        .with_weight(0.5),
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0, 0.3], kernel_core()),
+            growth(0.25, 0.03),
+        )
+        .with_radius_scale(0.6)
+        .with_weight(0.3),
+        LeniaKernel::new(
+            KernelShell::new(vec![0.5, 1.0], kernel_core()),
+            growth(0.11, 0.02),
+        )
+        .with_radius_scale(1.4)
+        .with_weight(0.2),
+    ];
+
+    let lenia_board = LeniaBoard::try_new(LeniaRule::from_kernels(kernels), SIZE, 15, 0.1, 100)?;
+
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                // uncomment for unthrottled FPS
+                // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .run();
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use lenia::*;
+
+const SIZE: (u32, u32) = (640, 360);
+const OUTPUT_DIR: &str = "initial_conditions";
+
+// Renders every initial condition on the CPU into `initial_conditions/`, without a GPU.
+// Run with `cargo run --example initial_conditions -- <seed>`
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .map(|seed| seed.parse().expect("seed must be an integer"))
+        .unwrap_or(42);
+    let orbium = LeniaPatternFile::load("assets/patterns/orbium.rle").unwrap();
+    let position = orbium.pattern.centered_in(SIZE);
+
+    let conditions = [
+        ("landscape", InitialCondition::default()),
+        (
+            "perlin",
+            InitialCondition::Perlin {
+                octaves: 2,
+                scale: 100.0,
+            },
+        ),
+        (
+            "fbm",
+            InitialCondition::Perlin {
+                octaves: 5,
+                scale: 200.0,
+            },
+        ),
+        (
+            "uniform",
+            InitialCondition::Uniform {
+                low: 0.0,
+                high: 1.0,
+            },
+        ),
+        (
+            "blobs",
+            InitialCondition::Blobs {
+                count: 20,
+                radius: 12.0,
+                region: 0.3,
+            },
+        ),
+        ("empty", InitialCondition::Empty),
+        (
+            "image",
+            InitialCondition::Image(InitialImage::load("assets/kernels/kernel.png").unwrap()),
+        ),
+        (
+            "patterns",
+            InitialCondition::Patterns(vec![PatternStamp::new(orbium.pattern, position)]),
+        ),
+    ];
+
+    std::fs::create_dir_all(OUTPUT_DIR).unwrap();
+    for (name, condition) in conditions {
+        let cells = condition.generate(SIZE, 1, seed);
+        let pixels = cells[0]
+            .iter()
+            .map(|cell| (cell.clamp(0.0, 1.0) * 255.0) as u8)
+            .collect();
+        let path = format!("{OUTPUT_DIR}/{name}.png");
+        image::GrayImage::from_raw(SIZE.0, SIZE.1, pixels)
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        println!("{path}");
+    }
+}
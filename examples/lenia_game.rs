@@ -2,17 +2,17 @@ use lenia::*;
 
 const SIZE: (u32, u32) = (1280, 720);
 
-fn main() {
+fn main() -> Result<(), LeniaError> {
     let lenia_kernel_core = Mapping::from_type(MappingType::GaussianCore { alpha: 4.0 });
 
-    let kernel_shell = KernelShell::new(vec![0.5, 2.0 / 3.0, 1.0], lenia_kernel_core);
+    let kernel_shell = KernelShell::try_new(vec![0.5, 2.0 / 3.0, 1.0], lenia_kernel_core)?;
     let kernel_image = KernelImage::new(&kernel_shell, 36, 1.0);
 
-    kernel_image
-        .save_image("lenia/assets/kernels/lenia_kernel_x2.png")
-        .unwrap();
+    if let Err(error) = kernel_image.save_image("lenia/assets/kernels/lenia_kernel_x2.png") {
+        eprintln!("kernel image not saved: {error}");
+    }
 
-    let lenia_board = LeniaBoard::new(
+    let lenia_board = LeniaBoard::try_new(
         LeniaRule::new(
             kernel_shell,
             Mapping::from_type(MappingType::GaussianGrowth {
@@ -24,7 +24,7 @@ fn main() {
         26,
         0.1,
         100,
-    );
+    )?;
 
     // lenia_board.get_growth_vector().iter().enumerate().for_each(|(i,x)| println!("{i:<2} : {x:.4}"));
     // println!("{:#?}", lenia_board.generate_params());
@@ -41,5 +41,8 @@ fn main() {
         }))
         .add_plugin(LeniaRenderPlugin::new(lenia_board))
         .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
         .run();
+
+    Ok(())
 }
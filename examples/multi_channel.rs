@@ -0,0 +1,68 @@
+use lenia::*;
+
+const SIZE: (u32, u32) = (1280, 720);
+
+fn main() -> Result<(), LeniaError> {
+    let kernel_core = || Mapping::from_type(MappingType::GaussianCore { alpha: 4.0 });
+    let growth = |mu, sigma| Mapping::from_type(MappingType::GaussianGrowth { mu, sigma });
+
+    // Three channels, each with a self-sustaining kernel and a kernel fed by its neighbour. The
+    // weights of the kernels growing a channel add up to 1.
+    let kernels = vec![
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0], kernel_core()),
+            growth(0.15, 0.017),
+        )
+        .with_weight(1.0 / 1.3),
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0, 0.5], kernel_core()),
+            growth(0.22, 0.03),
+        )
+        .with_channels(0, 1)
+        .with_weight(1.0 / 3.0),
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0], kernel_core()),
+            growth(0.15, 0.017),
+        )
+        .with_channels(1, 1)
+        .with_weight(2.0 / 3.0),
+        LeniaKernel::new(
+            KernelShell::new(vec![0.5, 1.0], kernel_core()),
+            growth(0.22, 0.03),
+        )
+        .with_channels(1, 2)
+        .with_weight(1.0 / 3.0),
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0], kernel_core()),
+            growth(0.15, 0.017),
+        )
+        .with_channels(2, 2)
+        .with_weight(2.0 / 3.0),
+        LeniaKernel::new(
+            KernelShell::new(vec![1.0, 1.0], kernel_core()),
+            growth(0.3, 0.05),
+        )
+        .with_channels(2, 0)
+        .with_weight(0.3 / 1.3),
+    ];
+
+    let lenia_board =
+        LeniaBoard::try_new(LeniaRule::multi_channel(3, kernels), SIZE, 13, 0.1, 100)?;
+
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                // uncomment for unthrottled FPS
+                // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .run();
+
+    Ok(())
+}
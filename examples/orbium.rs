@@ -0,0 +1,29 @@
+use lenia::*;
+
+const SIZE: (u32, u32) = (1280, 720);
+
+fn main() {
+    let orbium = LeniaPatternFile::load("assets/patterns/orbium.rle").unwrap();
+
+    let position = orbium.pattern.centered_in(SIZE);
+    let lenia_board = orbium
+        .params
+        .to_board(SIZE, 100)
+        .unwrap()
+        .with_pattern(PatternStamp::new(orbium.pattern, position));
+
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                // uncomment for unthrottled FPS
+                // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .run();
+}
@@ -0,0 +1,61 @@
+use lenia::*;
+
+// Run with `cargo run --example scenario -- assets/scenarios/orbium.lenia.toml`, optionally
+// followed by a gradient such as `assets/colormaps/ocean.toml`. Saving the scenario file while the
+// app runs applies its rule to the world. With `--features editor`, `E` opens a panel editing it.
+const DEFAULT_SCENARIO: &str = "assets/scenarios/expanded_lenia.lenia.ron";
+// Offered on the number keys, in this order.
+const RULES: [&str; 4] = [
+    "assets/scenarios/expanded_lenia.lenia.ron",
+    "assets/scenarios/game_of_life.lenia.toml",
+    "assets/scenarios/orbium.lenia.toml",
+    "assets/scenarios/discrete_lenia.lenia.toml",
+];
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_SCENARIO.to_string());
+    let lenia_board = Scenario::load(&path)
+        .and_then(|scenario| scenario.to_board())
+        .unwrap_or_else(|error| panic!("{path}: {error}"));
+    let rules = RULES
+        .iter()
+        .map(|path| Scenario::load(path).unwrap_or_else(|error| panic!("{path}: {error}")))
+        .collect();
+    // absolute, the asset server resolves relative paths against its own asset folder
+    let watched_path =
+        std::fs::canonicalize(&path).unwrap_or_else(|error| panic!("{path}: {error}"));
+    let colormap = std::env::args()
+        .nth(2)
+        .map_or_else(Colormap::default, |path| {
+            Colormap::load(&path).unwrap_or_else(|error| panic!("{path}: {error}"))
+        });
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(LeniaRules(rules))
+        .insert_resource(LeniaColors::new(colormap))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        // uncomment for unthrottled FPS
+                        // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .set(AssetPlugin {
+                    watch_for_changes: true,
+                    ..default()
+                }),
+        )
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .add_plugin(ScenarioReloadPlugin::new(watched_path.to_string_lossy()));
+    #[cfg(feature = "editor")]
+    app.add_plugin(LeniaEditorPlugin);
+    app.run();
+}
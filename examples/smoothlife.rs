@@ -2,17 +2,17 @@ use lenia::*;
 
 const SIZE: (u32, u32) = (1280, 720);
 
-fn main() {
+fn main() -> Result<(), LeniaError> {
     let smoothlife_kernel_core = Mapping::from_type(MappingType::StepCore);
 
-    let kernel_shell = KernelShell::new(vec![1.0], smoothlife_kernel_core);
+    let kernel_shell = KernelShell::try_new(vec![1.0], smoothlife_kernel_core)?;
     let kernel_image = KernelImage::new(&kernel_shell, 13, 1.0);
 
-    kernel_image
-        .save_image("lenia/assets/kernels/smoothlife_kernel_x2.png")
-        .unwrap();
+    if let Err(error) = kernel_image.save_image("lenia/assets/kernels/smoothlife_kernel_x2.png") {
+        eprintln!("kernel image not saved: {error}");
+    }
 
-    let lenia_board = LeniaBoard::new(
+    let lenia_board = LeniaBoard::try_new(
         LeniaRule::new(
             kernel_shell,
             Mapping::from_type(MappingType::GaussianGrowth {
@@ -24,13 +24,14 @@ fn main() {
         13,
         1.0,
         100,
-    );
+    )?;
 
     // lenia_board.get_growth_vector().iter().enumerate().for_each(|(i,x)| println!("{i:<2} : {x:.4}"));
     // println!("{:#?}", lenia_board.generate_params());
 
     App::new()
         .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(LeniaColors::new(Colormap::magma()))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 // uncomment for unthrottled FPS
@@ -41,5 +42,8 @@ fn main() {
         }))
         .add_plugin(LeniaRenderPlugin::new(lenia_board))
         .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
         .run();
+
+    Ok(())
 }
@@ -0,0 +1,44 @@
+use lenia::*;
+
+const SNAPSHOT_PATH: &str = "snapshot.lenia";
+
+// Press S to save the world to `snapshot.lenia` and L to restore it.
+fn main() {
+    let lenia_board = Scenario::load("assets/scenarios/orbium.lenia.toml")
+        .and_then(|scenario| scenario.to_board())
+        .unwrap();
+
+    App::new()
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                // uncomment for unthrottled FPS
+                // present_mode: bevy::window::PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugin(LeniaRenderPlugin::new(lenia_board))
+        .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
+        .add_system(snapshot_keys)
+        .run();
+}
+
+fn snapshot_keys(
+    keys: Res<Input<KeyCode>>,
+    mut take: EventWriter<TakeSnapshot>,
+    mut restore: EventWriter<RestoreSnapshot>,
+) {
+    if keys.just_pressed(KeyCode::S) {
+        take.send(TakeSnapshot {
+            path: Some(SNAPSHOT_PATH.into()),
+        });
+    }
+    if keys.just_pressed(KeyCode::L) {
+        match Snapshot::load(SNAPSHOT_PATH) {
+            Ok(snapshot) => restore.send(RestoreSnapshot(snapshot)),
+            Err(e) => error!("{SNAPSHOT_PATH}: {e}"),
+        }
+    }
+}
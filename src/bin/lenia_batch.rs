@@ -0,0 +1,322 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Instant,
+};
+
+use lenia::*;
+use serde::Serialize;
+
+const USAGE: &str = "\
+Runs a scenario on the CPU without a window, writing snapshots, statistics and frames.
+
+Usage: lenia-batch <scenario> [options]
+
+Options:
+  --steps <n>            steps to run [default: 1000]
+  --output <dir>         output directory [default: batch_output]
+  --seed <seed>          overrides the seed of the scenario
+  --snapshot-every <n>   writes `snapshot_<step>.lenia` every n steps, 0 for only the last [default: 0]
+  --stats-every <n>      measures the world every n steps, 0 to disable [default: 10]
+  --stats-format <fmt>   `csv` for `statistics.csv` or `json` for `statistics.jsonl` [default: csv]
+  --frames-every <n>     records a frame every n steps, 0 to disable [default: 0]
+  --frames-format <fmt>  `png` for a `frames/` sequence or `gif` for `recording.gif` [default: png]
+  --frame-delay <ms>     time each GIF frame is shown [default: 50]
+  --crop <x,y,w,h>       records only this region of the world, in cells
+  --downscale <n>        shrinks the frames by this factor [default: 1]
+  --colormap <file>      `.ron` or `.toml` gradient of the frames";
+
+struct BatchOptions {
+    scenario: PathBuf,
+    steps: u64,
+    output: PathBuf,
+    seed: Option<u64>,
+    snapshot_every: u64,
+    stats_every: u64,
+    stats_format: StatsFormat,
+    frames_every: u64,
+    recorder_settings: RecorderSettings,
+    colormap: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StatsFormat {
+    Csv,
+    Json, // one JSON object per line, so interrupted runs keep valid output
+}
+
+impl BatchOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut scenario = None;
+        let mut options = Self {
+            scenario: PathBuf::new(),
+            steps: 1000,
+            output: PathBuf::from("batch_output"),
+            seed: None,
+            snapshot_every: 0,
+            stats_every: 10,
+            stats_format: StatsFormat::Csv,
+            frames_every: 0,
+            recorder_settings: RecorderSettings::default().with_format(RecordFormat::PngSequence),
+            colormap: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for `{arg}`"));
+            let number = |value: String| {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("`{value}` is not a non-negative integer"))
+            };
+            match arg.as_str() {
+                "--steps" => options.steps = number(value()?)?,
+                "--output" => options.output = value()?.into(),
+                "--seed" => options.seed = Some(number(value()?)?),
+                "--snapshot-every" => options.snapshot_every = number(value()?)?,
+                "--stats-every" => options.stats_every = number(value()?)?,
+                "--stats-format" => {
+                    options.stats_format = match value()?.as_str() {
+                        "csv" => StatsFormat::Csv,
+                        "json" => StatsFormat::Json,
+                        format => return Err(format!("unknown statistics format `{format}`")),
+                    }
+                }
+                "--frames-every" => options.frames_every = number(value()?)?,
+                "--frames-format" => {
+                    let format = match value()?.as_str() {
+                        "png" => RecordFormat::PngSequence,
+                        "gif" => RecordFormat::Gif,
+                        format => return Err(format!("unknown frames format `{format}`")),
+                    };
+                    options.recorder_settings = options.recorder_settings.with_format(format);
+                }
+                "--frame-delay" => {
+                    let delay = number(value()?)?;
+                    let delay =
+                        u32::try_from(delay).map_err(|_| format!("delay `{delay}` is too long"))?;
+                    options.recorder_settings = options.recorder_settings.with_frame_delay(delay);
+                }
+                "--crop" => {
+                    let region = value()?;
+                    let crop = match region
+                        .split(',')
+                        .map(|value| value.trim().parse::<u32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .as_deref()
+                    {
+                        Ok(&[x, y, width, height]) => CropRegion {
+                            x,
+                            y,
+                            width,
+                            height,
+                        },
+                        _ => return Err(format!("crop region `{region}` is not `x,y,w,h`")),
+                    };
+                    options.recorder_settings = options.recorder_settings.with_crop(crop);
+                }
+                "--downscale" => {
+                    let downscale = number(value()?)?;
+                    let downscale = u32::try_from(downscale)
+                        .map_err(|_| format!("downscale `{downscale}` is too large"))?;
+                    options.recorder_settings = options.recorder_settings.with_downscale(downscale);
+                }
+                "--colormap" => options.colormap = Some(value()?.into()),
+                "--help" | "-h" => return Err(String::new()),
+                option if option.starts_with("--") => {
+                    return Err(format!("unknown option `{option}`"))
+                }
+                _ if scenario.is_none() => scenario = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument `{arg}`")),
+            }
+        }
+
+        options.scenario = scenario.ok_or("missing scenario file")?;
+        Ok(options)
+    }
+}
+
+/// Row of the statistics output.
+#[derive(Serialize)]
+struct StatisticsRow {
+    step: u64,
+    #[serde(flatten)]
+    statistics: ChannelStatistics,
+}
+
+struct StatisticsWriter {
+    format: StatsFormat,
+    writer: BufWriter<File>,
+}
+
+impl StatisticsWriter {
+    fn create(directory: &std::path::Path, format: StatsFormat) -> Result<Self, String> {
+        let name = match format {
+            StatsFormat::Csv => "statistics.csv",
+            StatsFormat::Json => "statistics.jsonl",
+        };
+        let path = directory.join(name);
+        let mut writer = BufWriter::new(
+            File::create(&path).map_err(|error| format!("{}: {error}", path.display()))?,
+        );
+        if format == StatsFormat::Csv {
+            writeln!(
+                writer,
+                "step,channel,mass,mean,max,occupied,centroid_x,centroid_y"
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        Ok(Self { format, writer })
+    }
+
+    fn write(&mut self, step: u64, statistics: ChannelStatistics) -> Result<(), String> {
+        let written = match self.format {
+            StatsFormat::Csv => {
+                let (x, y) = statistics
+                    .centroid
+                    .map_or((String::new(), String::new()), |(x, y)| {
+                        (x.to_string(), y.to_string())
+                    });
+                writeln!(
+                    self.writer,
+                    "{step},{},{},{},{},{},{x},{y}",
+                    statistics.channel,
+                    statistics.mass,
+                    statistics.mean,
+                    statistics.max,
+                    statistics.occupied
+                )
+            }
+            StatsFormat::Json => {
+                let row = serde_json::to_string(&StatisticsRow { step, statistics })
+                    .map_err(|error| error.to_string())?;
+                writeln!(self.writer, "{row}")
+            }
+        };
+        // flushed every time, an overnight run may be stopped at any point
+        written
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| error.to_string())
+    }
+}
+
+fn due(step: u64, every: u64) -> bool {
+    every > 0 && step.is_multiple_of(every)
+}
+
+fn run(options: BatchOptions) -> Result<(), String> {
+    let mut scenario = Scenario::load(&options.scenario)
+        .map_err(|error| format!("{}: {error}", options.scenario.display()))?;
+    // a fixed seed makes every snapshot reproducible with `Snapshot::replay`
+    let seed = options.seed.or(scenario.seed).unwrap_or_else(rand::random);
+    scenario.seed = Some(seed);
+    let board = scenario.to_board().map_err(|error| error.to_string())?;
+    for diagnostic in board.diagnose() {
+        eprintln!("{:?}: {diagnostic}", diagnostic.severity);
+    }
+    let mut cpu_lenia = CpuLenia::new(&board);
+    let colors = match &options.colormap {
+        Some(path) => LeniaColors::new(
+            Colormap::load(path).map_err(|error| format!("{}: {error}", path.display()))?,
+        ),
+        None => LeniaColors::default(),
+    };
+
+    fs::create_dir_all(&options.output)
+        .map_err(|error| format!("{}: {error}", options.output.display()))?;
+    scenario
+        .save(options.output.join("scenario.lenia.ron"))
+        .map_err(|error| error.to_string())?;
+    let mut statistics_writer = (options.stats_every > 0)
+        .then(|| StatisticsWriter::create(&options.output, options.stats_format))
+        .transpose()?;
+    let mut recorder = (options.frames_every > 0)
+        .then(|| {
+            let settings = options.recorder_settings.with_every(options.frames_every);
+            let path = match settings.format {
+                RecordFormat::PngSequence => options.output.join("frames"),
+                RecordFormat::Gif => options.output.join("recording.gif"),
+            };
+            Recorder::create(path, settings)
+        })
+        .transpose()
+        .map_err(|error| error.to_string())?;
+
+    println!(
+        "running {} for {} steps with seed {seed}",
+        options.scenario.display(),
+        options.steps
+    );
+    let size = cpu_lenia.get_space_resolution();
+    let started = Instant::now();
+    for step in 0..=options.steps {
+        if step > 0 {
+            cpu_lenia.step();
+        }
+        if step > 0 && step % 100 == 0 {
+            let rate = step as f64 / started.elapsed().as_secs_f64();
+            println!("step {step}/{} ({rate:.1} steps/s)", options.steps);
+        }
+
+        let stats_due = statistics_writer.is_some() && due(step, options.stats_every);
+        let frame_due = recorder
+            .as_ref()
+            .is_some_and(|recorder| recorder.is_due(step));
+        let snapshot_due = due(step, options.snapshot_every) || step == options.steps;
+        if !(stats_due || frame_due || snapshot_due) {
+            continue;
+        }
+        let channels: Vec<Vec<f32>> = (0..cpu_lenia.get_channels())
+            .map(|channel| cpu_lenia.cells(channel).to_vec())
+            .collect();
+
+        if let (true, Some(writer)) = (stats_due, &mut statistics_writer) {
+            for statistics in ChannelStatistics::measure_world(&channels, size) {
+                writer.write(step, statistics)?;
+            }
+        }
+        if let (true, Some(recorder)) = (frame_due, &mut recorder) {
+            recorder
+                .record(&colors.to_image(&channels, size))
+                .map_err(|error| error.to_string())?;
+        }
+        if snapshot_due {
+            let path = options.output.join(format!("snapshot_{step:08}.lenia"));
+            Snapshot::new(scenario.clone(), step, channels)
+                .and_then(|snapshot| snapshot.save(&path))
+                .map_err(|error| format!("{}: {error}", path.display()))?;
+        }
+    }
+
+    if let Some(recorder) = recorder {
+        let path = recorder.get_path().display().to_string();
+        println!("recorded {} frames to {path}", recorder.finish());
+    }
+    println!(
+        "done in {:.1}s, output in {}",
+        started.elapsed().as_secs_f64(),
+        options.output.display()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match BatchOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("{error}\n");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::{fmt, fs, path::Path};
+
+use serde::Deserialize;
+
+/// Number of entries of the lookup table uploaded to the display pass.
+pub const LUT_SIZE: usize = 256;
+
+/// A gradient over [0, 1] mapping cell values to colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colormap {
+    stops: Vec<(f32, [f32; 3])>, // (position, RGB), sorted by position
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColormapError {
+    Io(String),
+    Parse(String),
+    UnknownFormat(String), // file extension other than `ron` or `toml`
+    Invalid(String),
+}
+
+impl fmt::Display for ColormapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColormapError::Io(error) => write!(f, "{error}"),
+            ColormapError::Parse(error) => write!(f, "invalid gradient: {error}"),
+            ColormapError::UnknownFormat(extension) => {
+                write!(
+                    f,
+                    "unknown gradient format `{extension}`, expected `ron` or `toml`"
+                )
+            }
+            ColormapError::Invalid(error) => write!(f, "invalid gradient: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ColormapError {}
+
+// Gradient file, e.g. `stops = [{ position = 0.0, color = "#000000" }, ...]` in TOML.
+#[derive(Deserialize)]
+struct GradientFile {
+    stops: Vec<GradientStop>,
+}
+
+#[derive(Deserialize)]
+struct GradientStop {
+    position: f32,
+    color: String, // `#rrggbb`
+}
+
+impl Colormap {
+    /// Needs at least one stop, with positions in [0, 1] and in increasing order.
+    pub fn new(stops: Vec<(f32, [f32; 3])>) -> Result<Self, ColormapError> {
+        if stops.is_empty() {
+            return Err(ColormapError::Invalid("no color stops".to_string()));
+        }
+        if stops
+            .iter()
+            .any(|(position, _)| !(0.0..=1.0).contains(position))
+        {
+            return Err(ColormapError::Invalid(
+                "stop positions must be in [0, 1]".to_string(),
+            ));
+        }
+        if stops.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+            return Err(ColormapError::Invalid(
+                "stop positions must be increasing".to_string(),
+            ));
+        }
+        Ok(Self { stops })
+    }
+
+    /// Colors spread evenly over [0, 1], given as `0xrrggbb`.
+    fn evenly_spaced(colors: &[u32]) -> Self {
+        let last = (colors.len() - 1).max(1) as f32;
+        Self {
+            stops: colors
+                .iter()
+                .enumerate()
+                .map(|(index, color)| (index as f32 / last, rgb(*color)))
+                .collect(),
+        }
+    }
+
+    pub fn grayscale() -> Self {
+        Self::evenly_spaced(&[0x000000, 0xffffff])
+    }
+
+    pub fn viridis() -> Self {
+        Self::evenly_spaced(&[
+            0x440154, 0x482475, 0x414487, 0x355f8d, 0x2a788e, 0x21918c, 0x22a884, 0x44bf70,
+            0x7ad151, 0xbddf26, 0xfde725,
+        ])
+    }
+
+    pub fn magma() -> Self {
+        Self::evenly_spaced(&[
+            0x000004, 0x140e36, 0x3b0f70, 0x641a80, 0x8c2981, 0xb73779, 0xde4968, 0xf7705c,
+            0xfe9f6d, 0xfecf92, 0xfcfdbf,
+        ])
+    }
+
+    /// The jet-like map of the original Lenia, from dark blue through cyan and yellow to dark
+    /// red.
+    pub fn jet() -> Self {
+        Self::evenly_spaced(&[
+            0x000080, 0x0000ff, 0x0080ff, 0x00ffff, 0x80ff80, 0xffff00, 0xff8000, 0xff0000,
+            0x800000,
+        ])
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, ColormapError> {
+        Self::from_file(ron::from_str(ron).map_err(|e| ColormapError::Parse(e.to_string()))?)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ColormapError> {
+        Self::from_file(toml::from_str(toml).map_err(|e| ColormapError::Parse(e.to_string()))?)
+    }
+
+    /// Loads gradient stops from a `.ron` or `.toml` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ColormapError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| ColormapError::Io(e.to_string()))?;
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+        {
+            "ron" => Self::from_ron(&content),
+            "toml" => Self::from_toml(&content),
+            extension => Err(ColormapError::UnknownFormat(extension.to_string())),
+        }
+    }
+
+    fn from_file(file: GradientFile) -> Result<Self, ColormapError> {
+        let stops = file
+            .stops
+            .iter()
+            .map(|stop| {
+                let color = stop
+                    .color
+                    .strip_prefix('#')
+                    .filter(|hex| hex.len() == 6)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| {
+                        ColormapError::Parse(format!("`{}` is not a #rrggbb color", stop.color))
+                    })?;
+                Ok((stop.position, rgb(color)))
+            })
+            .collect::<Result<_, _>>()?;
+        Self::new(stops)
+    }
+
+    pub fn get_stops(&self) -> &[(f32, [f32; 3])] {
+        &self.stops
+    }
+
+    /// Color of `value`, interpolated linearly between the stops around it.
+    pub fn sample(&self, value: f32) -> [f32; 3] {
+        let next = self
+            .stops
+            .partition_point(|(position, _)| *position < value);
+        if next == 0 {
+            return self.stops[0].1;
+        }
+        if next == self.stops.len() {
+            return self.stops[next - 1].1;
+        }
+        let (start, low) = self.stops[next - 1];
+        let (end, high) = self.stops[next];
+        let t = if end > start {
+            (value - start) / (end - start)
+        } else {
+            1.0
+        };
+        [0, 1, 2].map(|i| low[i] + (high[i] - low[i]) * t)
+    }
+
+    /// `size` colors sampled evenly over [0, 1], as uploaded to the display pass. Empty for a
+    /// `size` of 0, only the color of 0 for 1.
+    pub fn to_lut(&self, size: usize) -> Vec<[f32; 3]> {
+        let last = size.saturating_sub(1).max(1) as f32;
+        (0..size)
+            .map(|index| self.sample(index as f32 / last))
+            .collect()
+    }
+}
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Self::jet()
+    }
+}
+
+fn rgb(color: u32) -> [f32; 3] {
+    [16, 8, 0].map(|shift| ((color >> shift) & 0xff) as f32 / 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_interpolate_between_stops() {
+        let colormap = Colormap::new(vec![(0.0, [0.0; 3]), (0.5, [1.0, 0.0, 0.5])]).unwrap();
+        assert_eq!(colormap.sample(-1.0), [0.0; 3]);
+        assert_eq!(colormap.sample(0.25), [0.5, 0.0, 0.25]);
+        assert_eq!(colormap.sample(0.5), [1.0, 0.0, 0.5]);
+        assert_eq!(colormap.sample(2.0), [1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn lookup_tables_span_the_gradient() {
+        let colormap = Colormap::viridis();
+        assert!(colormap.to_lut(0).is_empty());
+        assert_eq!(colormap.to_lut(1), vec![rgb(0x440154)]);
+        let lut = colormap.to_lut(LUT_SIZE);
+        assert_eq!(lut.len(), LUT_SIZE);
+        assert_eq!((lut[0], lut[LUT_SIZE - 1]), (rgb(0x440154), rgb(0xfde725)));
+    }
+
+    #[test]
+    fn rejects_invalid_stops() {
+        assert!(Colormap::new(vec![]).is_err());
+        assert!(Colormap::new(vec![(1.5, [0.0; 3])]).is_err());
+        assert!(Colormap::new(vec![(0.5, [0.0; 3]), (0.2, [1.0; 3])]).is_err());
+        assert!(matches!(
+            Colormap::from_toml(r##"stops = [{ position = 0.0, color = "#12345" }]"##),
+            Err(ColormapError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn loads_gradient_files() {
+        let colormap = Colormap::from_toml(
+            r##"
+            stops = [
+                { position = 0.0, color = "#000000" },
+                { position = 1.0, color = "#ff8000" },
+            ]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(colormap.sample(1.0), [1.0, 128.0 / 255.0, 0.0]);
+        assert!(Colormap::load("assets/colormaps/ocean.toml").is_ok());
+        assert!(matches!(
+            Colormap::load("assets/creatures/animals.json"),
+            Err(ColormapError::UnknownFormat(_))
+        ));
+    }
+}
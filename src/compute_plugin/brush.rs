@@ -0,0 +1,180 @@
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource, render_asset::RenderAssets, render_resource::*,
+        renderer::RenderDevice,
+    },
+};
+use bytemuck::{cast_slice, NoUninit};
+
+use super::{LeniaRenderPipeline, LeniaStateImages, StateFormat};
+use crate::lenia_plugin::ActiveLeniaRule;
+
+/// Footprint of a brush. `Gaussian` fades out towards its radius.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BrushShape {
+    #[default]
+    Circle,
+    Square,
+    Gaussian,
+}
+
+/// How a brush combines its value with the cells under it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BrushMode {
+    #[default]
+    Add, // add the value, clamped to 1
+    Set,   // replace the cells with the value
+    Erase, // clear the cells
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Brush {
+    pub shape: BrushShape,
+    pub mode: BrushMode,
+    pub radius: f32, // in cells
+    pub value: f32,
+    pub channel: u32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            shape: BrushShape::Circle,
+            mode: BrushMode::Add,
+            radius: 8.0,
+            value: 0.5,
+            channel: 0,
+        }
+    }
+}
+
+/// Event applying `brush` around `position`, in cells from the top left corner of the world.
+///
+/// The strokes of a frame are applied in order by the `brush` pass before that frame's updates.
+pub struct PaintWorld {
+    pub position: Vec2,
+    pub brush: Brush,
+}
+
+/// Entry of the strokes storage buffer read by the `brush` shader.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, NoUninit)]
+pub(super) struct LeniaGPUStroke {
+    center: [f32; 2],
+    radius: f32,
+    value: f32,
+    shape: u32,
+    mode: u32,
+    channel: u32,
+    _padding: u32,
+}
+
+impl From<&PaintWorld> for LeniaGPUStroke {
+    fn from(event: &PaintWorld) -> Self {
+        Self {
+            center: event.position.into(),
+            radius: event.brush.radius,
+            value: event.brush.value,
+            shape: event.brush.shape as u32,
+            mode: event.brush.mode as u32,
+            channel: event.brush.channel,
+            _padding: 0,
+        }
+    }
+}
+
+/// Strokes of the latest frame that painted any, applied by the render world whenever the
+/// generation changes.
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub struct LeniaBrushStrokes {
+    pub(super) generation: u32,
+    pub(super) strokes: Vec<LeniaGPUStroke>,
+}
+
+/// `[i]` paints over state image i into the other one, which is then copied back.
+#[derive(Resource)]
+pub(super) struct LeniaBrushBindGroups {
+    pub bind_groups: [BindGroup; 2],
+    pub generation: u32,
+}
+
+/// Strokes on a channel the state does not store, or the active rule does not use, are dropped.
+pub(super) fn collect_strokes(
+    mut paint_events: EventReader<PaintWorld>,
+    mut brush_strokes: ResMut<LeniaBrushStrokes>,
+    state_format: Res<StateFormat>,
+    rule: Res<ActiveLeniaRule>,
+) {
+    let channels = rule.get_channels().min(state_format.channels());
+    let strokes: Vec<_> = paint_events
+        .iter()
+        .filter(|event| {
+            let in_range = event.brush.channel < channels;
+            if !in_range {
+                warn!(
+                    "brush stroke on channel {} dropped, the world has {channels} channel(s)",
+                    event.brush.channel
+                );
+            }
+            in_range
+        })
+        .map(LeniaGPUStroke::from)
+        .collect();
+    if !strokes.is_empty() {
+        brush_strokes.generation += 1;
+        brush_strokes.strokes = strokes;
+    }
+}
+
+pub(super) fn queue_brush_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline: Res<LeniaRenderPipeline>,
+    gpu_images: Res<RenderAssets<Image>>,
+    state_images: Res<LeniaStateImages>,
+    brush_strokes: Res<LeniaBrushStrokes>,
+) {
+    if !brush_strokes.is_changed() || brush_strokes.strokes.is_empty() {
+        return;
+    }
+    let (Some(first), Some(second)) = (
+        gpu_images.get(&state_images.0[0]),
+        gpu_images.get(&state_images.0[1]),
+    ) else {
+        return;
+    };
+
+    let strokes_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("brush strokes buffer"),
+        contents: cast_slice(&brush_strokes.strokes),
+        usage: BufferUsages::STORAGE,
+    });
+    let bind_group = |state: &TextureView, painted: &TextureView| {
+        render_device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.brush_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(state),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: strokes_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(painted),
+                },
+            ],
+        })
+    };
+    commands.insert_resource(LeniaBrushBindGroups {
+        bind_groups: [
+            bind_group(&first.texture_view, &second.texture_view),
+            bind_group(&second.texture_view, &first.texture_view),
+        ],
+        generation: brush_strokes.generation,
+    });
+}
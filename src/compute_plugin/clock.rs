@@ -0,0 +1,166 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use bevy::{prelude::*, render::extract_resource::ExtractResource};
+
+/// Upper bound on the updates dispatched in one frame, so a slow frame does not snowball.
+pub const MAX_STEPS_PER_FRAME: u32 = 64;
+
+/// How fast the simulation advances while running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepRate {
+    PerFrame(u32),  // fixed number of updates every rendered frame
+    PerSecond(f32), // fixed timestep, independent of the frame rate
+}
+
+/// Drives the simulation: the node dispatches `steps_this_frame` updates every frame.
+///
+/// The step counter is shared with the render world, where the node advances it. The main
+/// world changes it through `LeniaStepReset`.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct SimulationClock {
+    paused: bool,
+    rate: StepRate,
+    requested_steps: u32, // single steps requested while paused
+    accumulator: f32,     // fraction of a step carried over between frames by `PerSecond`
+    steps_this_frame: u32,
+    step: Arc<AtomicU64>,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new(StepRate::PerFrame(1))
+    }
+}
+
+impl SimulationClock {
+    pub fn new(rate: StepRate) -> Self {
+        Self {
+            paused: false,
+            rate,
+            requested_steps: 0,
+            accumulator: 0.0,
+            steps_this_frame: 0,
+            step: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Starts the simulation paused.
+    pub fn paused(self) -> Self {
+        Self {
+            paused: true,
+            ..self
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.accumulator = 0.0;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume()
+        } else {
+            self.pause()
+        }
+    }
+
+    /// Runs a single update on the next frame, only while paused.
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.requested_steps += 1;
+        }
+    }
+
+    pub fn get_rate(&self) -> StepRate {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: StepRate) {
+        self.rate = rate;
+        self.accumulator = 0.0;
+    }
+
+    /// Updates run so far.
+    pub fn get_step(&self) -> u64 {
+        self.step.load(Ordering::Relaxed)
+    }
+
+    /// Only called from the render world, the node picks the state texture by the step parity.
+    pub(crate) fn set_step(&self, step: u64) {
+        self.step.store(step, Ordering::Relaxed)
+    }
+
+    pub fn get_steps_this_frame(&self) -> u32 {
+        self.steps_this_frame
+    }
+
+    /// Counts the updates dispatched by the node.
+    pub(crate) fn advance_step(&self, steps: u32) {
+        self.step.fetch_add(steps as u64, Ordering::Relaxed);
+    }
+
+    fn tick(&mut self, delta_seconds: f32) {
+        let steps = if self.paused {
+            std::mem::take(&mut self.requested_steps)
+        } else {
+            match self.rate {
+                StepRate::PerFrame(steps) => steps,
+                StepRate::PerSecond(steps_per_second) => {
+                    self.accumulator += delta_seconds * steps_per_second;
+                    let steps = self.accumulator.floor();
+                    self.accumulator -= steps;
+                    steps as u32
+                }
+            }
+        };
+        if steps > MAX_STEPS_PER_FRAME {
+            self.accumulator = 0.0;
+        }
+        self.steps_this_frame = steps.min(MAX_STEPS_PER_FRAME);
+    }
+}
+
+/// Step the counter restarts from, e.g. after restoring a snapshot, applied in the render world
+/// before any update of the frame whenever `generation` changes.
+#[derive(Resource, Clone, Copy, Default, ExtractResource)]
+pub struct LeniaStepReset {
+    pub generation: u32,
+    pub step: u64,
+}
+
+impl LeniaStepReset {
+    pub fn request(&mut self, step: u64) {
+        self.generation += 1;
+        self.step = step;
+    }
+}
+
+pub(super) fn apply_step_reset(
+    reset: Option<Res<LeniaStepReset>>,
+    clock: Option<Res<SimulationClock>>,
+    mut applied_generation: Local<u32>,
+) {
+    let (Some(reset), Some(clock)) = (reset, clock) else {
+        return;
+    };
+    if reset.generation != *applied_generation {
+        *applied_generation = reset.generation;
+        clock.set_step(reset.step);
+    }
+}
+
+pub(super) fn tick_clock(mut clock: ResMut<SimulationClock>, time: Res<Time>) {
+    clock.tick(time.delta_seconds());
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    prelude::*,
+    render::{extract_resource::ExtractResource, render_resource::*, renderer::RenderDevice},
+};
+use bytemuck::{cast_slice, NoUninit};
+
+use super::fields::LeniaView;
+use crate::colormap::{Colormap, LUT_SIZE};
+use crate::lenia_plugin::lenia_rules::MAX_CHANNELS;
+
+/// How the display pass colors the world: single channel worlds go through `colormap`, the
+/// others add up `channel_colors` weighted by their channel values.
+#[derive(Resource, Clone, Debug, PartialEq, ExtractResource)]
+pub struct LeniaColors {
+    pub colormap: Colormap,
+    pub channel_colors: [[f32; 3]; MAX_CHANNELS as usize], // RGB of each channel
+}
+
+impl Default for LeniaColors {
+    fn default() -> Self {
+        Self::new(Colormap::default())
+    }
+}
+
+impl LeniaColors {
+    pub fn new(colormap: Colormap) -> Self {
+        Self {
+            colormap,
+            channel_colors: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn with_channel_colors(self, channel_colors: [[f32; 3]; MAX_CHANNELS as usize]) -> Self {
+        Self {
+            channel_colors,
+            ..self
+        }
+    }
+
+    /// CPU counterpart of the display pass showing the state, for row-major `channels` of a
+    /// world of `size`.
+    pub fn to_image(&self, channels: &[Vec<f32>], size: (u32, u32)) -> image::RgbImage {
+        let lut = self.colormap.to_lut(LUT_SIZE);
+        image::RgbImage::from_fn(size.0, size.1, |x, y| {
+            let index = (y * size.0 + x) as usize;
+            let color = match channels {
+                [cells] => {
+                    let value = cells[index].clamp(0.0, 1.0);
+                    lut[(value * (LUT_SIZE - 1) as f32).round() as usize]
+                }
+                _ => channels.iter().zip(&self.channel_colors).fold(
+                    [0.0; 3],
+                    |color, (cells, channel_color)| {
+                        let value = cells[index].clamp(0.0, 1.0);
+                        [0, 1, 2].map(|i| color[i] + value * channel_color[i])
+                    },
+                ),
+            };
+            image::Rgb(color.map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8))
+        })
+    }
+}
+
+/// Uniform of the display pass, next to the colormap lookup table.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, NoUninit)]
+struct LeniaGPUDisplay {
+    channel_colors: [[f32; 4]; MAX_CHANNELS as usize],
+    lut_size: u32,
+    field: u32,        // `FieldView` discriminant
+    side_by_side: u32, // 1 to show every field in a grid
+    _padding: u32,
+}
+
+#[derive(Resource)]
+pub(super) struct LeniaColorBuffers {
+    pub lut: Buffer,
+    pub display: Buffer,
+}
+
+pub(super) fn display_uniform_size() -> u64 {
+    std::mem::size_of::<LeniaGPUDisplay>() as u64
+}
+
+/// Uploads the colors again whenever they or the view change.
+pub(super) fn prepare_colors(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    colors: Res<LeniaColors>,
+    view: Res<LeniaView>,
+) {
+    if !colors.is_changed() && !view.is_changed() {
+        return;
+    }
+
+    let lut: Vec<[f32; 4]> = colors
+        .colormap
+        .to_lut(LUT_SIZE)
+        .into_iter()
+        .map(|[r, g, b]| [r, g, b, 1.0])
+        .collect();
+    let display = LeniaGPUDisplay {
+        channel_colors: colors.channel_colors.map(|[r, g, b]| [r, g, b, 1.0]),
+        lut_size: LUT_SIZE as u32,
+        field: view.field as u32,
+        side_by_side: view.side_by_side as u32,
+        _padding: 0,
+    };
+
+    commands.insert_resource(LeniaColorBuffers {
+        lut: render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("colormap buffer"),
+            contents: cast_slice(&lut),
+            usage: BufferUsages::STORAGE,
+        }),
+        display: render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("display buffer"),
+            contents: cast_slice(&[display]),
+            usage: BufferUsages::UNIFORM,
+        }),
+    });
+}
@@ -0,0 +1,90 @@
+use bevy::{
+    prelude::*,
+    render::{extract_resource::ExtractResource, render_resource::*},
+};
+
+/// Layers of the fields texture, one per field after `FieldView::State`.
+pub const FIELD_LAYERS: u32 = 3;
+
+/// What the display pass shows of the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldView {
+    #[default]
+    State,
+    Potential, // U, the kernel weighted neighbourhood of every cell
+    Growth,    // G(U) in [-1, 1]
+    Delta,     // change of the last step, shown relative to `dt`
+}
+
+impl FieldView {
+    pub const ALL: [FieldView; 4] = [
+        FieldView::State,
+        FieldView::Potential,
+        FieldView::Growth,
+        FieldView::Delta,
+    ];
+
+    /// The view after this one, wrapping around to `State`.
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// Selects the field shown on the world sprite, or all four of them in a 2x2 grid at half
+/// resolution: state, potential, growth and delta from the top left.
+///
+/// The potential, growth and delta are only written while one of them is shown. They are
+/// written by the last step of a frame, so they stay empty until the simulation steps.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractResource)]
+pub struct LeniaView {
+    pub field: FieldView,
+    pub side_by_side: bool,
+}
+
+impl LeniaView {
+    pub fn new(field: FieldView) -> Self {
+        Self {
+            field,
+            side_by_side: false,
+        }
+    }
+
+    pub fn with_side_by_side(self, side_by_side: bool) -> Self {
+        Self {
+            side_by_side,
+            ..self
+        }
+    }
+
+    /// Whether the `update` pass has to write the fields texture.
+    pub fn shows_fields(&self) -> bool {
+        self.side_by_side || self.field != FieldView::State
+    }
+}
+
+/// Potential, growth and delta of the last step in the layers of one texture, with a channel
+/// per RGB component like the state.
+#[derive(Resource, Clone, Deref, ExtractResource)]
+pub struct LeniaFieldsImage(pub Handle<Image>);
+
+pub(super) fn create_fields_image(size: (u32, u32)) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: FIELD_LAYERS,
+        },
+        TextureDimension::D2,
+        &[0; 16],
+        TextureFormat::Rgba32Float,
+    );
+    image.texture_descriptor.usage = TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::STORAGE_BINDING
+        | TextureUsages::TEXTURE_BINDING;
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        ..default()
+    });
+    image
+}
@@ -1,6 +1,18 @@
-use std::borrow::Cow;
+pub mod brush;
+pub mod clock;
+pub mod colors;
+pub mod fields;
+pub mod readback;
+pub mod recording;
+pub mod rule;
+
+use std::{
+    borrow::Cow,
+    sync::{mpsc, Mutex},
+};
 
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
     render::{
         extract_resource::{ExtractResource, ExtractResourcePlugin},
@@ -12,27 +24,99 @@ use bevy::{
     },
 };
 
+use self::brush::LeniaBrushBindGroups;
+pub use self::brush::{Brush, BrushMode, BrushShape, LeniaBrushStrokes, PaintWorld};
+pub use self::clock::{LeniaStepReset, SimulationClock, StepRate, MAX_STEPS_PER_FRAME};
+use self::colors::LeniaColorBuffers;
+pub use self::colors::LeniaColors;
+use self::fields::create_fields_image;
+pub use self::fields::{FieldView, LeniaFieldsImage, LeniaView, FIELD_LAYERS};
+use self::readback::{LeniaReadbackReceiver, LeniaReadbackSender, SnapshotPaths};
+pub use self::readback::{LeniaReadbackRequest, RestoreSnapshot, SnapshotTaken, TakeSnapshot};
+pub use self::recording::{LeniaRecorder, ToggleRecording};
+pub use self::rule::ApplyScenario;
+use crate::initial_condition::{noise_offset, InitialCondition};
+use crate::lenia_plugin::lenia_rules::{check_world_size, quantize_cells, MAX_CHANNELS};
 use crate::lenia_plugin::params::{
-    LeniaGPUGrowthArrayBuffer, LeniaGPUParams, LeniaGPUParamsBuffer, LeniaGPUTexture,
+    LeniaGPUGrowthArrayBuffer, LeniaGPUKernel, LeniaGPUKernelsBuffer, LeniaGPUParams,
+    LeniaGPUParamsBuffer, LeniaGPUTexture,
 };
+use crate::lenia_plugin::ActiveLeniaRule;
+use crate::scenario::Scenario;
+use half::f16;
+use serde::{Deserialize, Serialize};
 
-const SIZE: (u32, u32) = (1280, 720);
 const WORKGROUP_SIZE: u32 = 8;
 
 pub struct LeniaComputePlugin;
 
 impl Plugin for LeniaComputePlugin {
     fn build(&self, app: &mut App) {
+        // `LeniaRenderPlugin` has already reported why there is no world.
+        if !app.world.contains_resource::<LeniaWorldSize>() {
+            error!("LeniaComputePlugin needs a world from LeniaRenderPlugin, added first");
+            return;
+        }
         // Extract the game of life image resource from the main world into the render world
         // for operation on by the compute shader and display on the sprite.
-        app.add_plugin(ExtractResourcePlugin::<LeniaImage>::default())
-            .add_startup_system(setup);
+        app.add_plugin(ExtractResourcePlugin::<LeniaStateImages>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaDisplayImage>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaFieldsImage>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaWorldSize>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaResize>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaSeeding>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaReseed>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaBrushStrokes>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaReadbackRequest>::default())
+            .add_plugin(ExtractResourcePlugin::<SimulationClock>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaStepReset>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaColors>::default())
+            .add_plugin(ExtractResourcePlugin::<LeniaView>::default())
+            .add_event::<ResizeWorld>()
+            .add_event::<ReseedWorld>()
+            .add_event::<PaintWorld>()
+            .add_event::<TakeSnapshot>()
+            .add_event::<SnapshotTaken>()
+            .add_event::<RestoreSnapshot>()
+            .add_event::<ToggleRecording>()
+            .add_event::<ApplyScenario>()
+            .add_startup_system(setup)
+            .add_system(clock::tick_clock)
+            .add_system(resize_world)
+            .add_system(reseed_world)
+            .add_system(brush::collect_strokes)
+            .add_system(readback::request_snapshots)
+            .add_system(readback::receive_snapshots)
+            .add_system(readback::restore_snapshots)
+            .add_system(recording::toggle_recording)
+            .add_system(recording::request_frames)
+            .add_system(recording::record_frames)
+            .add_system(rule::apply_scenarios.in_base_set(CoreSet::PreUpdate));
+
+        // Kept if the app inserted its own clock, e.g. to start paused.
+        let (sender, receiver) = mpsc::channel();
+        app.init_resource::<SimulationClock>()
+            .init_resource::<LeniaStepReset>()
+            .init_resource::<LeniaReadbackRequest>()
+            .init_resource::<LeniaBrushStrokes>()
+            .init_resource::<LeniaColors>()
+            .init_resource::<LeniaView>()
+            .init_resource::<SnapshotPaths>()
+            .init_resource::<LeniaRecorder>()
+            .insert_resource(LeniaReadbackReceiver(Mutex::new(receiver)));
 
         let render_app = app.sub_app_mut(RenderApp);
 
         render_app
+            .insert_resource(LeniaReadbackSender(sender))
             .init_resource::<LeniaRenderPipeline>()
-            .add_system(queue_bind_group.in_set(RenderSet::Queue));
+            .add_system(clock::apply_step_reset.in_set(RenderSet::Prepare))
+            .add_system(colors::prepare_colors.in_set(RenderSet::Prepare))
+            .add_system(queue_bind_group.in_set(RenderSet::Queue))
+            .add_system(queue_resize_bind_group.in_set(RenderSet::Queue))
+            .add_system(queue_display_bind_group.in_set(RenderSet::Queue))
+            .add_system(brush::queue_brush_bind_groups.in_set(RenderSet::Queue))
+            .add_system(readback::readback_world.in_set(RenderSet::Cleanup));
 
         let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
         render_graph.add_node("lenia", LeniaNode::default());
@@ -40,11 +124,48 @@ impl Plugin for LeniaComputePlugin {
     }
 }
 
-fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+fn setup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    world_size: Res<LeniaWorldSize>,
+    state_format: Res<StateFormat>,
+    initial_state: Option<Res<LeniaInitialState>>,
+) {
+    let mut image = state_format.create_image(world_size.0);
+    if let Some(initial_state) = &initial_state {
+        image.data = state_format.encode(&initial_state.channels);
+        commands.insert_resource(LeniaSeeding::Uploaded);
+    } else {
+        commands.insert_resource(LeniaSeeding::Shader);
+    }
+    let state_images = [images.add(image.clone()), images.add(image)];
+    let display_image = images.add(create_display_image(world_size.0));
+    let fields_image = images.add(create_fields_image(world_size.0));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(world_size.0 .0 as f32, world_size.0 .1 as f32)),
+                ..default()
+            },
+            texture: display_image.clone(),
+            ..default()
+        },
+        LeniaWorldSprite,
+    ));
+    commands.spawn(Camera2dBundle::default());
+
+    commands.insert_resource(LeniaStateImages(state_images));
+    commands.insert_resource(LeniaDisplayImage(display_image));
+    commands.insert_resource(LeniaFieldsImage(fields_image));
+}
+
+/// The 8-bit image shown on the world sprite, written by the `display` pass from the state.
+fn create_display_image(size: (u32, u32)) -> Image {
     let mut image = Image::new_fill(
         Extent3d {
-            width: SIZE.0,
-            height: SIZE.1,
+            width: size.0,
+            height: size.1,
             depth_or_array_layers: 1,
         },
         TextureDimension::D2,
@@ -53,74 +174,560 @@ fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
     );
     image.texture_descriptor.usage =
         TextureUsages::COPY_DST | TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
-    let image = images.add(image);
+    image
+}
 
-    commands.spawn(SpriteBundle {
-        sprite: Sprite {
-            custom_size: Some(Vec2::new(SIZE.0 as f32, SIZE.1 as f32)),
-            ..default()
-        },
-        texture: image.clone(),
-        ..default()
+/// The state, fields and display images of the world, replaced together whenever the world is.
+#[derive(SystemParam)]
+pub struct WorldImages<'w, 's> {
+    images: ResMut<'w, Assets<Image>>,
+    state_images: ResMut<'w, LeniaStateImages>,
+    display_image: ResMut<'w, LeniaDisplayImage>,
+    fields_image: ResMut<'w, LeniaFieldsImage>,
+    world_size: ResMut<'w, LeniaWorldSize>,
+    sprites:
+        Query<'w, 's, (&'static mut Sprite, &'static mut Handle<Image>), With<LeniaWorldSprite>>,
+}
+
+impl<'w, 's> WorldImages<'w, 's> {
+    pub fn get_size(&self) -> (u32, u32) {
+        self.world_size.0
+    }
+
+    /// Swaps in a new world whose state images both start as `state`, along with a display
+    /// and a fields image of its size, returning the previous state images.
+    pub fn replace(&mut self, state: Image) -> [Handle<Image>; 2] {
+        let size = (state.size().x as u32, state.size().y as u32);
+        let display_image = self.images.add(create_display_image(size));
+        let state_images = [self.images.add(state.clone()), self.images.add(state)];
+        let previous = std::mem::replace(&mut self.state_images.0, state_images);
+        self.display_image.0 = display_image.clone();
+        self.fields_image.0 = self.images.add(create_fields_image(size));
+        self.world_size.0 = size;
+
+        for (mut sprite, mut texture) in &mut self.sprites {
+            sprite.custom_size = Some(Vec2::new(size.0 as f32, size.1 as f32));
+            *texture = display_image.clone();
+        }
+        previous
+    }
+}
+
+fn resize_world(
+    mut commands: Commands,
+    mut resize_events: EventReader<ResizeWorld>,
+    mut world_images: WorldImages,
+    state_format: Res<StateFormat>,
+    active_rule: Res<ActiveLeniaRule>,
+    last_resize: Option<Res<LeniaResize>>,
+) {
+    let Some(event) = resize_events.iter().last() else {
+        return;
+    };
+    if let Err(error) = check_world_size(event.size, active_rule.get_kernel_diameter()) {
+        error!("world not resized: {error}");
+        return;
+    }
+
+    let previous = world_images.replace(state_format.create_image(event.size));
+
+    commands.insert_resource(LeniaResize {
+        previous,
+        mode: event.mode,
+        generation: last_resize.map_or(1, |resize| resize.generation + 1),
     });
-    commands.spawn(Camera2dBundle::default());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reseed_world(
+    mut commands: Commands,
+    mut reseed_events: EventReader<ReseedWorld>,
+    mut world_images: WorldImages,
+    state_format: Res<StateFormat>,
+    seeding: Res<LeniaSeeding>,
+    initial_state: Option<Res<LeniaInitialState>>,
+    mut step_reset: ResMut<LeniaStepReset>,
+    mut scenario: ResMut<LeniaScenario>,
+    last_reseed: Option<Res<LeniaReseed>>,
+) {
+    let Some(event) = reseed_events.iter().last() else {
+        return;
+    };
+    let seed = event.seed.unwrap_or_else(rand::random);
+    info!("reseeding with seed {seed}");
+    scenario.0.seed = Some(seed);
 
-    commands.insert_resource(LeniaImage(image));
+    match initial_state {
+        Some(initial_state) if *seeding == LeniaSeeding::Uploaded => {
+            // the world may have been resized since it started
+            let size = world_images.get_size();
+            let mut channels =
+                initial_state
+                    .condition
+                    .generate(size, initial_state.channels.len() as u32, seed);
+            quantize_cells(&mut channels, initial_state.quantization);
+            let mut image = state_format.create_image(size);
+            image.data = state_format.encode(&channels);
+            world_images.replace(image);
+            step_reset.request(0);
+        }
+        // the node runs `init` again and restarts the step counter
+        _ => commands.insert_resource(LeniaReseed {
+            generation: last_reseed.map_or(1, |reseed| reseed.generation + 1),
+            noise_offset: noise_offset(seed),
+        }),
+    }
 }
 
+/// The two world state textures, in the `StateFormat` of the board. Every update reads one and
+/// writes the other, so the state after `step` updates is in `[step % 2]`.
 #[derive(Resource, Clone, Deref, ExtractResource)]
-pub struct LeniaImage(pub Handle<Image>);
+pub struct LeniaStateImages(pub [Handle<Image>; 2]);
+
+impl LeniaStateImages {
+    pub fn current(&self, step: u64) -> &Handle<Image> {
+        &self.0[(step % 2) as usize]
+    }
+}
+
+/// The image shown on the world sprite.
+#[derive(Resource, Clone, Deref, ExtractResource)]
+pub struct LeniaDisplayImage(pub Handle<Image>);
+
+/// Texture format of the world state. Channels are stored in the red, green and blue
+/// components, `R32Float` only fits single-channel rules.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateFormat {
+    R32Float,
+    Rgba16Float,
+    #[default]
+    Rgba32Float,
+}
 
+impl StateFormat {
+    pub fn texture_format(&self) -> TextureFormat {
+        match self {
+            StateFormat::R32Float => TextureFormat::R32Float,
+            StateFormat::Rgba16Float => TextureFormat::Rgba16Float,
+            StateFormat::Rgba32Float => TextureFormat::Rgba32Float,
+        }
+    }
+
+    /// Number of channels the format can hold.
+    pub fn channels(&self) -> u32 {
+        match self {
+            StateFormat::R32Float => 1,
+            StateFormat::Rgba16Float | StateFormat::Rgba32Float => MAX_CHANNELS,
+        }
+    }
+
+    /// Selects the storage texture declaration in the shaders.
+    fn shader_def(&self) -> &'static str {
+        match self {
+            StateFormat::R32Float => "STATE_R32FLOAT",
+            StateFormat::Rgba16Float => "STATE_RGBA16FLOAT",
+            StateFormat::Rgba32Float => "STATE_RGBA32FLOAT",
+        }
+    }
+
+    pub fn texel_size(&self) -> usize {
+        self.texture_format().describe().block_size as usize
+    }
+
+    pub fn create_image(&self, size: (u32, u32)) -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &vec![0; self.texel_size()],
+            self.texture_format(),
+        );
+        image.texture_descriptor.usage = TextureUsages::COPY_DST
+            | TextureUsages::COPY_SRC
+            | TextureUsages::STORAGE_BINDING
+            | TextureUsages::TEXTURE_BINDING;
+        image
+    }
+
+    /// Packs row-major channels into texture data.
+    pub fn encode(&self, channels: &[Vec<f32>]) -> Vec<u8> {
+        let texel = |index: usize| {
+            let channel = |c: usize| channels.get(c).map_or(0.0, |cells| cells[index]);
+            [channel(0), channel(1), channel(2), 1.0]
+        };
+        (0..channels[0].len())
+            .flat_map(|index| match self {
+                StateFormat::R32Float => texel(index)[0].to_le_bytes().to_vec(),
+                StateFormat::Rgba16Float => texel(index)
+                    .iter()
+                    .flat_map(|value| f16::from_f32(*value).to_le_bytes())
+                    .collect(),
+                StateFormat::Rgba32Float => texel(index)
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Unpacks one texel of texture data into its RGBA components.
+    pub fn decode(&self, texel: &[u8]) -> [f32; 4] {
+        let component = |index: usize| match self {
+            StateFormat::R32Float | StateFormat::Rgba32Float => {
+                f32::from_le_bytes(texel[index * 4..index * 4 + 4].try_into().unwrap())
+            }
+            StateFormat::Rgba16Float => {
+                f16::from_le_bytes([texel[index * 2], texel[index * 2 + 1]]).to_f32()
+            }
+        };
+        match self {
+            StateFormat::R32Float => [component(0), 0.0, 0.0, 1.0],
+            _ => [component(0), component(1), component(2), component(3)],
+        }
+    }
+}
+
+/// Resolution of the world texture as (width, height), taken from the `LeniaBoard`.
+#[derive(Resource, Clone, Copy, Deref, ExtractResource)]
+pub struct LeniaWorldSize(pub (u32, u32));
+
+/// Description of the running rule, recorded in snapshots.
+#[derive(Resource, Clone, Deref)]
+pub struct LeniaScenario(pub Scenario);
+
+/// Initial world state computed on the CPU, uploaded in place of running the `init` shader.
+#[derive(Resource)]
+pub struct LeniaInitialState {
+    pub condition: InitialCondition, // generated again with a new seed, at the current size
+    pub quantization: Option<u32>,   // levels regenerated cells are rounded to
+    pub channels: Vec<Vec<f32>>,
+}
+
+/// Whether the world is seeded by the `init` shader or uploaded from `LeniaInitialState`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, ExtractResource)]
+pub enum LeniaSeeding {
+    Shader,
+    Uploaded,
+}
+
+/// Noise offset of the latest reseed of a shader-seeded world, replacing the one of the board.
+#[derive(Resource, Clone, Copy, ExtractResource)]
+pub struct LeniaReseed {
+    pub generation: u32,
+    pub noise_offset: Vec3,
+}
+
+/// Event restarting the world from its initial condition at step 0 with a new seed, random if
+/// `None`. Snapshots record the new seed.
+pub struct ReseedWorld {
+    pub seed: Option<u64>,
+}
+
+/// Marks the sprite displaying the world texture.
+#[derive(Component)]
+pub struct LeniaWorldSprite;
+
+/// How the current state is carried over when the world is resized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeMode {
+    Crop,     // keep the centered overlap, cell for cell
+    Resample, // stretch the whole world over the new size
+}
+
+/// Event requesting a new world size at runtime.
+pub struct ResizeWorld {
+    pub size: (u32, u32),
+    pub mode: ResizeMode,
+}
+
+/// Latest resize, keeping the previous world image alive so its state can be copied over.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct LeniaResize {
+    pub previous: [Handle<Image>; 2],
+    pub mode: ResizeMode,
+    pub generation: u32,
+}
+
+/// Update bind groups, `[i]` reads state image `i` and writes the other one.
+#[derive(Resource)]
+pub struct LeniaImageBindGroups(pub [BindGroup; 2]);
+
+/// Display bind groups, `[i]` shows state image `i`.
 #[derive(Resource)]
-pub struct LeniaImageBindGroup(pub BindGroup);
+pub struct LeniaDisplayBindGroups(pub [BindGroup; 2]);
+
+#[derive(Resource)]
+pub struct LeniaResizeBindGroup {
+    pub bind_group: BindGroup,
+    pub generation: u32,
+}
 
+#[allow(clippy::too_many_arguments)]
 fn queue_bind_group(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
     pipeline: Res<LeniaRenderPipeline>,
     gpu_images: Res<RenderAssets<Image>>,
-    lenia_image: Res<LeniaImage>,
+    state_images: Res<LeniaStateImages>,
+    fields_image: Res<LeniaFieldsImage>,
     kernel_texture: Res<LeniaGPUTexture>,
     params_buffer: Res<LeniaGPUParamsBuffer>,
     growth_array_buffer: Res<LeniaGPUGrowthArrayBuffer>,
+    kernels_buffer: Res<LeniaGPUKernelsBuffer>,
 ) {
-    let view = &gpu_images[&lenia_image.0];
+    // Freshly created world images may not be prepared yet.
+    let (Some(first), Some(second), Some(fields)) = (
+        gpu_images.get(&state_images.0[0]),
+        gpu_images.get(&state_images.0[1]),
+        gpu_images.get(&fields_image.0),
+    ) else {
+        return;
+    };
+    let bind_group = |previous: &TextureView, next: &TextureView| {
+        render_device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.texture_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(previous),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(&kernel_texture.texture_views[0]),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: growth_array_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: kernels_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 5,
+                    resource: BindingResource::TextureView(next),
+                },
+                BindGroupEntry {
+                    binding: 6,
+                    resource: BindingResource::TextureView(&fields.texture_view),
+                },
+            ],
+        })
+    };
+    commands.insert_resource(LeniaImageBindGroups([
+        bind_group(&first.texture_view, &second.texture_view),
+        bind_group(&second.texture_view, &first.texture_view),
+    ]));
+}
+
+fn queue_resize_bind_group(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline: Res<LeniaRenderPipeline>,
+    gpu_images: Res<RenderAssets<Image>>,
+    state_images: Res<LeniaStateImages>,
+    clock: Res<SimulationClock>,
+    resize: Option<Res<LeniaResize>>,
+) {
+    let Some(resize) = resize else {
+        return;
+    };
+    // The copy lands in the state image the next update reads from.
+    let current = (clock.get_step() % 2) as usize;
+    let (Some(previous_view), Some(view)) = (
+        gpu_images.get(&resize.previous[current]),
+        gpu_images.get(&state_images.0[current]),
+    ) else {
+        return;
+    };
     let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
         label: None,
-        layout: &pipeline.texture_bind_group_layout,
+        layout: &pipeline.resize_bind_group_layout,
         entries: &[
             BindGroupEntry {
                 binding: 0,
-                resource: BindingResource::TextureView(&view.texture_view),
+                resource: BindingResource::TextureView(&previous_view.texture_view),
             },
             BindGroupEntry {
                 binding: 1,
-                resource: params_buffer.as_entire_binding(),
-            },
-            BindGroupEntry {
-                binding: 2,
-                resource: BindingResource::TextureView(&kernel_texture.texture_views[0]),
-            },
-            BindGroupEntry {
-                binding: 3,
-                resource: growth_array_buffer.as_entire_binding(),
+                resource: BindingResource::TextureView(&view.texture_view),
             },
         ],
     });
-    commands.insert_resource(LeniaImageBindGroup(bind_group));
+    commands.insert_resource(LeniaResizeBindGroup {
+        bind_group,
+        generation: resize.generation,
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_display_bind_group(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline: Res<LeniaRenderPipeline>,
+    gpu_images: Res<RenderAssets<Image>>,
+    state_images: Res<LeniaStateImages>,
+    display_image: Res<LeniaDisplayImage>,
+    fields_image: Res<LeniaFieldsImage>,
+    params_buffer: Res<LeniaGPUParamsBuffer>,
+    color_buffers: Option<Res<LeniaColorBuffers>>,
+) {
+    let (Some(first), Some(second), Some(display_view), Some(fields), Some(color_buffers)) = (
+        gpu_images.get(&state_images.0[0]),
+        gpu_images.get(&state_images.0[1]),
+        gpu_images.get(&display_image.0),
+        gpu_images.get(&fields_image.0),
+        color_buffers,
+    ) else {
+        return;
+    };
+    let bind_group = |state: &TextureView| {
+        render_device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.display_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(state),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&display_view.texture_view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: color_buffers.lut.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: color_buffers.display.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 5,
+                    resource: BindingResource::TextureView(&fields.texture_view),
+                },
+            ],
+        })
+    };
+    commands.insert_resource(LeniaDisplayBindGroups([
+        bind_group(&first.texture_view),
+        bind_group(&second.texture_view),
+    ]));
 }
 
 #[derive(Resource)]
 pub struct LeniaRenderPipeline {
     pub texture_bind_group_layout: BindGroupLayout,
+    pub resize_bind_group_layout: BindGroupLayout,
+    pub display_bind_group_layout: BindGroupLayout,
+    pub brush_bind_group_layout: BindGroupLayout,
     pub init_pipeline: CachedComputePipelineId,
     pub update_pipeline: CachedComputePipelineId,
+    pub update_fields_pipeline: CachedComputePipelineId, // also writes the fields texture
+    pub crop_pipeline: CachedComputePipelineId,
+    pub resample_pipeline: CachedComputePipelineId,
+    pub display_pipeline: CachedComputePipelineId,
+    pub brush_pipeline: CachedComputePipelineId,
 }
 
 impl FromWorld for LeniaRenderPipeline {
     fn from_world(world: &mut World) -> Self {
-        let params = world.resource::<LeniaGPUParams>();
-        let texture_bind_group_layout =
+        let state_format = *world.resource::<StateFormat>();
+        let texture_bind_group_layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            &BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Texture {
+                            sample_type: TextureSampleType::Float { filterable: false },
+                            view_dimension: TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: BufferSize::new(
+                                std::mem::size_of::<LeniaGPUParams>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::ReadOnly,
+                            format: TextureFormat::Rgba32Float,
+                            view_dimension: TextureViewDimension::D2Array,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            // one entry, the active rule may be swapped for a larger one
+                            min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: BufferSize::new(
+                                std::mem::size_of::<LeniaGPUKernel>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 5,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: state_format.texture_format(),
+                            view_dimension: TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 6,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::StorageTexture {
+                            access: StorageTextureAccess::WriteOnly,
+                            format: TextureFormat::Rgba32Float,
+                            view_dimension: TextureViewDimension::D2Array,
+                        },
+                        count: None,
+                    },
+                ],
+            },
+        );
+        let resize_bind_group_layout =
             world
                 .resource::<RenderDevice>()
                 .create_bind_group_layout(&BindGroupLayoutDescriptor {
@@ -129,45 +736,128 @@ impl FromWorld for LeniaRenderPipeline {
                         BindGroupLayoutEntry {
                             binding: 0,
                             visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Texture {
+                                sample_type: TextureSampleType::Float { filterable: false },
+                                view_dimension: TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: ShaderStages::COMPUTE,
                             ty: BindingType::StorageTexture {
-                                access: StorageTextureAccess::ReadWrite,
-                                format: TextureFormat::Rgba8Unorm,
+                                access: StorageTextureAccess::WriteOnly,
+                                format: state_format.texture_format(),
+                                view_dimension: TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+        let display_bind_group_layout =
+            world
+                .resource::<RenderDevice>()
+                .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Texture {
+                                sample_type: TextureSampleType::Float { filterable: false },
                                 view_dimension: TextureViewDimension::D2,
+                                multisampled: false,
                             },
                             count: None,
                         },
                         BindGroupLayoutEntry {
                             binding: 1,
                             visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::StorageTexture {
+                                access: StorageTextureAccess::WriteOnly,
+                                format: TextureFormat::Rgba8Unorm,
+                                view_dimension: TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: ShaderStages::COMPUTE,
                             ty: BindingType::Buffer {
                                 ty: BufferBindingType::Uniform,
                                 has_dynamic_offset: false,
                                 min_binding_size: BufferSize::new(
-                                    6 * std::mem::size_of::<f32>() as u64,
+                                    std::mem::size_of::<LeniaGPUParams>() as u64,
                                 ),
                             },
                             count: None,
                         },
                         BindGroupLayoutEntry {
-                            binding: 2,
+                            binding: 3,
                             visibility: ShaderStages::COMPUTE,
-                            ty: BindingType::StorageTexture {
-                                access: StorageTextureAccess::ReadOnly,
-                                format: TextureFormat::Rgba32Float,
+                            ty: BindingType::Buffer {
+                                ty: BufferBindingType::Storage { read_only: true },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 4,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Buffer {
+                                ty: BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: BufferSize::new(colors::display_uniform_size()),
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 5,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Texture {
+                                sample_type: TextureSampleType::Float { filterable: false },
+                                view_dimension: TextureViewDimension::D2Array,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+        let brush_bind_group_layout =
+            world
+                .resource::<RenderDevice>()
+                .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Texture {
+                                sample_type: TextureSampleType::Float { filterable: false },
                                 view_dimension: TextureViewDimension::D2,
+                                multisampled: false,
                             },
                             count: None,
                         },
                         BindGroupLayoutEntry {
-                            binding: 3,
+                            binding: 1,
                             visibility: ShaderStages::COMPUTE,
                             ty: BindingType::Buffer {
                                 ty: BufferBindingType::Storage { read_only: true },
                                 has_dynamic_offset: false,
-                                min_binding_size: BufferSize::new(
-                                    params.growth_resolution as u64
-                                        * std::mem::size_of::<f32>() as u64,
-                                ),
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::StorageTexture {
+                                access: StorageTextureAccess::WriteOnly,
+                                format: state_format.texture_format(),
+                                view_dimension: TextureViewDimension::D2,
                             },
                             count: None,
                         },
@@ -179,28 +869,88 @@ impl FromWorld for LeniaRenderPipeline {
         let update_shader = world
             .resource::<AssetServer>()
             .load("shaders/update_lenia.wgsl");
+        let resize_shader = world
+            .resource::<AssetServer>()
+            .load("shaders/resize_lenia.wgsl");
+        let display_shader = world
+            .resource::<AssetServer>()
+            .load("shaders/display_lenia.wgsl");
+        let brush_shader = world
+            .resource::<AssetServer>()
+            .load("shaders/brush_lenia.wgsl");
+        let shader_defs = vec![state_format.shader_def().into()];
         let pipeline_cache = world.resource::<PipelineCache>();
         let init_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
             label: None,
             layout: vec![texture_bind_group_layout.clone()],
             push_constant_ranges: Vec::new(),
             shader: init_shader,
-            shader_defs: vec![],
+            shader_defs: shader_defs.clone(),
             entry_point: Cow::from("init"),
         });
         let update_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
             label: None,
             layout: vec![texture_bind_group_layout.clone()],
             push_constant_ranges: Vec::new(),
-            shader: update_shader,
-            shader_defs: vec![],
+            shader: update_shader.clone(),
+            shader_defs: shader_defs.clone(),
             entry_point: Cow::from("update"),
         });
+        let update_fields_pipeline =
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: None,
+                layout: vec![texture_bind_group_layout.clone()],
+                push_constant_ranges: Vec::new(),
+                shader: update_shader,
+                shader_defs: [shader_defs.clone(), vec!["WRITE_FIELDS".into()]].concat(),
+                entry_point: Cow::from("update"),
+            });
+
+        let crop_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: None,
+            layout: vec![resize_bind_group_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: resize_shader.clone(),
+            shader_defs: shader_defs.clone(),
+            entry_point: Cow::from("crop"),
+        });
+        let resample_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: None,
+            layout: vec![resize_bind_group_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: resize_shader,
+            shader_defs: shader_defs.clone(),
+            entry_point: Cow::from("resample"),
+        });
+        let brush_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: None,
+            layout: vec![brush_bind_group_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: brush_shader,
+            shader_defs,
+            entry_point: Cow::from("brush"),
+        });
+        let display_pipeline = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: None,
+            layout: vec![display_bind_group_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: display_shader,
+            shader_defs: vec![],
+            entry_point: Cow::from("display"),
+        });
 
         LeniaRenderPipeline {
             texture_bind_group_layout,
+            resize_bind_group_layout,
+            display_bind_group_layout,
+            brush_bind_group_layout,
             init_pipeline,
             update_pipeline,
+            update_fields_pipeline,
+            crop_pipeline,
+            resample_pipeline,
+            display_pipeline,
+            brush_pipeline,
         }
     }
 }
@@ -213,12 +963,24 @@ pub enum LeniaRenderState {
 
 pub struct LeniaNode {
     pub state: LeniaRenderState,
+    resized_generation: u32,  // last `LeniaResize` copied into the new world
+    resize_pending: bool,     // copy the previous world over during this run
+    reseeded_generation: u32, // last `LeniaReseed` the `init` shader ran for
+    reseed_pending: bool,     // run `init` again during this run
+    painted_generation: u32,  // last `LeniaBrushStrokes` applied to the world
+    brush_pending: bool,      // apply the brush strokes during this run
 }
 
 impl Default for LeniaNode {
     fn default() -> Self {
         Self {
             state: LeniaRenderState::Loading,
+            resized_generation: 0,
+            resize_pending: false,
+            reseeded_generation: 0,
+            reseed_pending: false,
+            painted_generation: 0,
+            brush_pending: false,
         }
     }
 }
@@ -246,6 +1008,45 @@ impl render_graph::Node for LeniaNode {
             }
             LeniaRenderState::Update => {}
         }
+
+        // copy the previous world once its bind group and the resize pipelines are ready
+        self.resize_pending = false;
+        if let Some(resize_bind_group) = world.get_resource::<LeniaResizeBindGroup>() {
+            let resize_pipelines_ready = [pipeline.crop_pipeline, pipeline.resample_pipeline]
+                .into_iter()
+                .all(|id| {
+                    matches!(
+                        pipeline_cache.get_compute_pipeline_state(id),
+                        CachedPipelineState::Ok(_)
+                    )
+                });
+            if resize_bind_group.generation != self.resized_generation && resize_pipelines_ready {
+                self.resized_generation = resize_bind_group.generation;
+                self.resize_pending = true;
+            }
+        }
+
+        // reseeding before the first `init` only changes the noise it is run with
+        self.reseed_pending = false;
+        if let Some(reseed) = world.get_resource::<LeniaReseed>() {
+            if reseed.generation != self.reseeded_generation {
+                self.reseeded_generation = reseed.generation;
+                self.reseed_pending = matches!(self.state, LeniaRenderState::Update);
+            }
+        }
+
+        self.brush_pending = false;
+        if let Some(brush_bind_groups) = world.get_resource::<LeniaBrushBindGroups>() {
+            if brush_bind_groups.generation != self.painted_generation
+                && matches!(
+                    pipeline_cache.get_compute_pipeline_state(pipeline.brush_pipeline),
+                    CachedPipelineState::Ok(_)
+                )
+            {
+                self.painted_generation = brush_bind_groups.generation;
+                self.brush_pending = true;
+            }
+        }
     }
 
     fn run(
@@ -254,35 +1055,124 @@ impl render_graph::Node for LeniaNode {
         render_context: &mut RenderContext,
         world: &World,
     ) -> Result<(), render_graph::NodeRunError> {
-        let texture_bind_group = &world.resource::<LeniaImageBindGroup>().0;
+        let Some(texture_bind_groups) = world.get_resource::<LeniaImageBindGroups>() else {
+            return Ok(());
+        };
         let pipeline_cache = world.resource::<PipelineCache>();
         let pipeline = world.resource::<LeniaRenderPipeline>();
+        let Some(clock) = world.get_resource::<SimulationClock>() else {
+            return Ok(());
+        };
+        let (width, height) = world.resource::<LeniaWorldSize>().0;
+        let workgroups = (
+            width.div_ceil(WORKGROUP_SIZE),
+            height.div_ceil(WORKGROUP_SIZE),
+        );
+
+        let encoder = render_context.command_encoder();
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+
+        if self.resize_pending {
+            let resize_pipeline = match world.resource::<LeniaResize>().mode {
+                ResizeMode::Crop => pipeline.crop_pipeline,
+                ResizeMode::Resample => pipeline.resample_pipeline,
+            };
+            pass.set_bind_group(0, &world.resource::<LeniaResizeBindGroup>().bind_group, &[]);
+            pass.set_pipeline(
+                pipeline_cache
+                    .get_compute_pipeline(resize_pipeline)
+                    .unwrap(),
+            );
+            pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+        }
 
-        let mut pass = render_context
-            .command_encoder()
-            .begin_compute_pass(&ComputePassDescriptor::default());
+        if self.reseed_pending {
+            clock.set_step(0);
+        }
+        // the state lives in `[step % 2]`, the bind group of the other image writes into it
+        let current = (clock.get_step() % 2) as usize;
+
+        // paint into the other image and copy it back, the pass cannot read and write one texture
+        let gpu_images = world.resource::<RenderAssets<Image>>();
+        let state_images = world.resource::<LeniaStateImages>();
+        if let (true, Some(state), Some(painted)) = (
+            self.brush_pending,
+            gpu_images.get(&state_images.0[current]),
+            gpu_images.get(&state_images.0[1 - current]),
+        ) {
+            let brush_bind_groups = world.resource::<LeniaBrushBindGroups>();
+            pass.set_bind_group(0, &brush_bind_groups.bind_groups[current], &[]);
+            pass.set_pipeline(
+                pipeline_cache
+                    .get_compute_pipeline(pipeline.brush_pipeline)
+                    .unwrap(),
+            );
+            pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+            drop(pass);
 
-        pass.set_bind_group(0, texture_bind_group, &[]);
+            encoder.copy_texture_to_texture(
+                painted.texture.as_image_copy(),
+                state.texture.as_image_copy(),
+                Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+        }
+
+        let shader_seeded = world.get_resource::<LeniaSeeding>() != Some(&LeniaSeeding::Uploaded);
+        if matches!(self.state, LeniaRenderState::Init) && shader_seeded || self.reseed_pending {
+            let init_pipeline = pipeline_cache
+                .get_compute_pipeline(pipeline.init_pipeline)
+                .unwrap();
+            pass.set_bind_group(0, &texture_bind_groups.0[1 - current], &[]);
+            pass.set_pipeline(init_pipeline);
+            pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+        }
 
         // select the pipeline based on the current state
         match self.state {
-            LeniaRenderState::Loading => {}
-            LeniaRenderState::Init => {
-                let init_pipeline = pipeline_cache
-                    .get_compute_pipeline(pipeline.init_pipeline)
-                    .unwrap();
-                pass.set_pipeline(init_pipeline);
-                pass.dispatch_workgroups(SIZE.0 / WORKGROUP_SIZE, SIZE.1 / WORKGROUP_SIZE, 1);
-            }
+            LeniaRenderState::Loading | LeniaRenderState::Init => {}
             LeniaRenderState::Update => {
                 let update_pipeline = pipeline_cache
                     .get_compute_pipeline(pipeline.update_pipeline)
                     .unwrap();
-                pass.set_pipeline(update_pipeline);
-                pass.dispatch_workgroups(SIZE.0 / WORKGROUP_SIZE, SIZE.1 / WORKGROUP_SIZE, 1);
+                // only the last update of the frame writes the fields that are shown
+                let update_fields_pipeline = world
+                    .get_resource::<LeniaView>()
+                    .filter(|view| view.shows_fields())
+                    .and_then(|_| {
+                        pipeline_cache.get_compute_pipeline(pipeline.update_fields_pipeline)
+                    });
+                // every update reads the generation the previous one wrote
+                let steps = clock.get_steps_this_frame();
+                for step in 0..steps as usize {
+                    match update_fields_pipeline {
+                        Some(fields_pipeline) if step + 1 == steps as usize => {
+                            pass.set_pipeline(fields_pipeline)
+                        }
+                        _ => pass.set_pipeline(update_pipeline),
+                    }
+                    pass.set_bind_group(0, &texture_bind_groups.0[(current + step) % 2], &[]);
+                    pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+                }
+                clock.advance_step(steps);
             }
         }
 
+        // convert the state into the displayed image
+        if let (Some(display_bind_groups), Some(display_pipeline)) = (
+            world.get_resource::<LeniaDisplayBindGroups>(),
+            pipeline_cache.get_compute_pipeline(pipeline.display_pipeline),
+        ) {
+            let current = (clock.get_step() % 2) as usize;
+            pass.set_bind_group(0, &display_bind_groups.0[current], &[]);
+            pass.set_pipeline(display_pipeline);
+            pass.dispatch_workgroups(workgroups.0, workgroups.1, 1);
+        }
+
         Ok(())
     }
 }
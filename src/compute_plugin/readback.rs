@@ -0,0 +1,220 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    path::PathBuf,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::ExtractResource,
+        render_asset::RenderAssets,
+        render_resource::*,
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+use super::{
+    LeniaScenario, LeniaStateImages, LeniaStepReset, SimulationClock, StateFormat, WorldImages,
+};
+use crate::snapshot::Snapshot;
+
+/// Event requesting a snapshot of the running world, saved to `path` if given.
+///
+/// The world texture is read back at the end of the frame and the snapshot is delivered with a
+/// `SnapshotTaken` event during the next one.
+pub struct TakeSnapshot {
+    pub path: Option<PathBuf>,
+}
+
+pub struct SnapshotTaken(pub Snapshot);
+
+/// Event replacing the world state with a snapshot and resetting the step counter to its step.
+///
+/// The running rule is kept, a snapshot taken with a different rule only restores the cells.
+pub struct RestoreSnapshot(pub Snapshot);
+
+/// Incremented for every `TakeSnapshot` frame, the render world reads back the world texture
+/// whenever it changes.
+#[derive(Resource, Clone, Copy, Default, ExtractResource)]
+pub struct LeniaReadbackRequest {
+    pub generation: u32,
+}
+
+/// Destination of the requested snapshots by request generation.
+#[derive(Resource, Default)]
+pub(super) struct SnapshotPaths(HashMap<u32, PathBuf>);
+
+/// World texture read back on the render world, as RGBA texels.
+pub(super) struct WorldReadback {
+    generation: u32,
+    size: (u32, u32),
+    step: u64,
+    texels: Vec<[f32; 4]>,
+}
+
+#[derive(Resource, Deref)]
+pub(super) struct LeniaReadbackSender(pub Sender<WorldReadback>);
+
+#[derive(Resource, Deref)]
+pub(super) struct LeniaReadbackReceiver(pub Mutex<Receiver<WorldReadback>>);
+
+pub(super) fn request_snapshots(
+    mut events: EventReader<TakeSnapshot>,
+    mut request: ResMut<LeniaReadbackRequest>,
+    mut paths: ResMut<SnapshotPaths>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    request.generation += 1;
+    for event in events.iter() {
+        if let Some(path) = &event.path {
+            paths.0.insert(request.generation, path.clone());
+        }
+    }
+}
+
+pub(super) fn receive_snapshots(
+    receiver: Res<LeniaReadbackReceiver>,
+    scenario: Res<LeniaScenario>,
+    mut paths: ResMut<SnapshotPaths>,
+    mut snapshots: EventWriter<SnapshotTaken>,
+) {
+    let receiver = receiver.lock().unwrap();
+    while let Ok(readback) = receiver.try_recv() {
+        // Channels are stored in the red, green and blue components, a single one as gray.
+        let channels = (0..scenario.channels as usize)
+            .map(|channel| readback.texels.iter().map(|texel| texel[channel]).collect())
+            .collect();
+        let mut scenario = scenario.0.clone();
+        scenario.world_size = readback.size;
+        let path = paths.0.remove(&readback.generation);
+        let snapshot = match Snapshot::new(scenario, readback.step, channels) {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                error!("failed to read back the world: {error}");
+                continue;
+            }
+        };
+
+        if let Some(path) = path {
+            match snapshot.save(&path) {
+                Ok(()) => info!("snapshot of step {} saved to {path:?}", snapshot.step),
+                Err(e) => error!("failed to save snapshot to {path:?}: {e}"),
+            }
+        }
+        snapshots.send(SnapshotTaken(snapshot));
+    }
+}
+
+pub(super) fn restore_snapshots(
+    mut events: EventReader<RestoreSnapshot>,
+    mut world_images: WorldImages,
+    state_format: Res<StateFormat>,
+    scenario: Res<LeniaScenario>,
+    mut step_reset: ResMut<LeniaStepReset>,
+) {
+    let Some(RestoreSnapshot(snapshot)) = events.iter().last() else {
+        return;
+    };
+    if snapshot.channels.len() != scenario.channels as usize {
+        warn!(
+            "cannot restore a {} channel snapshot into a {} channel world",
+            snapshot.channels.len(),
+            scenario.channels
+        );
+        return;
+    }
+    if !scenario.same_rule(&snapshot.scenario) {
+        warn!("snapshot was taken with a different rule, only its cells are restored");
+    }
+
+    let mut image = state_format.create_image(snapshot.scenario.world_size);
+    image.data = state_format.encode(&snapshot.channels);
+    world_images.replace(image);
+    step_reset.request(snapshot.step);
+}
+
+/// Copies the world texture into a mappable buffer once per request, after the frame's update
+/// has been submitted, and waits for it.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn readback_world(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    gpu_images: Res<RenderAssets<Image>>,
+    state_images: Res<LeniaStateImages>,
+    state_format: Res<StateFormat>,
+    request: Res<LeniaReadbackRequest>,
+    clock: Res<SimulationClock>,
+    sender: Res<LeniaReadbackSender>,
+    mut last_generation: Local<u32>,
+) {
+    if request.generation == *last_generation {
+        return;
+    }
+    let step = clock.get_step();
+    let Some(gpu_image) = gpu_images.get(state_images.current(step)) else {
+        return;
+    };
+    *last_generation = request.generation;
+
+    let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+    let texel_size = state_format.texel_size();
+    let bytes_per_row = RenderDevice::align_copy_bytes_per_row(width as usize * texel_size);
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: (bytes_per_row * height as usize) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        gpu_image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(bytes_per_row as u32),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    render_queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    render_device.map_buffer(&slice, MapMode::Read, |result| {
+        if let Err(e) = result {
+            error!("failed to map the world readback buffer: {e}");
+        }
+    });
+    render_device.poll(wgpu::Maintain::Wait);
+
+    let texels = slice
+        .get_mapped_range()
+        .chunks_exact(bytes_per_row)
+        .flat_map(|row| {
+            row[..width as usize * texel_size]
+                .chunks_exact(texel_size)
+                .map(|texel| state_format.decode(texel))
+        })
+        .collect();
+    buffer.unmap();
+
+    let _ = sender.send(WorldReadback {
+        generation: request.generation,
+        size: (width, height),
+        step,
+        texels,
+    });
+}
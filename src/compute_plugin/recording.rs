@@ -0,0 +1,140 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::AppExit, prelude::*};
+
+use super::{LeniaColors, SimulationClock, SnapshotTaken, TakeSnapshot};
+use crate::recorder::{RecordFormat, Recorder, RecorderSettings};
+
+/// Event starting a recording with the settings of `LeniaRecorder`, or finishing the running one.
+pub struct ToggleRecording;
+
+/// Records the colored world of the running app every `settings.every` steps.
+///
+/// Frames are read back from the GPU like snapshots, so with several steps per frame they are
+/// taken at the first step past every multiple of `every`. Every recording gets a new
+/// `lenia_<timestamp>` GIF or PNG directory in `directory`.
+#[derive(Resource)]
+pub struct LeniaRecorder {
+    pub settings: RecorderSettings,
+    pub directory: PathBuf,
+    recorder: Option<Recorder>,
+    next_capture: u64, // step of the next frame
+    awaiting: bool,    // a readback for the next frame is on its way
+}
+
+impl Default for LeniaRecorder {
+    fn default() -> Self {
+        Self::new(RecorderSettings::default(), "recordings")
+    }
+}
+
+impl LeniaRecorder {
+    pub fn new(settings: RecorderSettings, directory: impl Into<PathBuf>) -> Self {
+        Self {
+            settings,
+            directory: directory.into(),
+            recorder: None,
+            next_capture: 0,
+            awaiting: false,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    fn start(&mut self, step: u64) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let name = match self.settings.format {
+            RecordFormat::PngSequence => format!("lenia_{timestamp}"),
+            RecordFormat::Gif => format!("lenia_{timestamp}.gif"),
+        };
+        match Recorder::create(self.directory.join(name), self.settings) {
+            Ok(recorder) => {
+                info!("recording to {:?}", recorder.get_path());
+                self.recorder = Some(recorder);
+                self.next_capture = step;
+                self.awaiting = false;
+            }
+            Err(e) => error!("failed to start recording: {e}"),
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            let path = recorder.get_path().to_path_buf();
+            info!("recorded {} frames to {path:?}", recorder.finish());
+        }
+    }
+}
+
+pub(super) fn toggle_recording(
+    mut toggle_events: EventReader<ToggleRecording>,
+    mut exit_events: EventReader<AppExit>,
+    mut recorder: ResMut<LeniaRecorder>,
+    clock: Res<SimulationClock>,
+) {
+    // completes the GIF before the app closes
+    if !exit_events.is_empty() {
+        exit_events.clear();
+        recorder.finish();
+    }
+    if toggle_events.is_empty() {
+        return;
+    }
+    toggle_events.clear();
+    if recorder.is_recording() {
+        recorder.finish();
+    } else {
+        recorder.start(clock.get_step());
+    }
+}
+
+pub(super) fn request_frames(
+    mut recorder: ResMut<LeniaRecorder>,
+    clock: Res<SimulationClock>,
+    mut take_events: EventWriter<TakeSnapshot>,
+) {
+    if !recorder.is_recording() || recorder.awaiting {
+        return;
+    }
+    // the step counter restarts on reseeds and restored snapshots
+    let step = clock.get_step();
+    if step + recorder.settings.every < recorder.next_capture {
+        recorder.next_capture = step;
+    }
+    if step >= recorder.next_capture {
+        take_events.send(TakeSnapshot { path: None });
+        recorder.awaiting = true;
+    }
+}
+
+pub(super) fn record_frames(
+    mut snapshot_events: EventReader<SnapshotTaken>,
+    mut recorder: ResMut<LeniaRecorder>,
+    colors: Res<LeniaColors>,
+) {
+    for SnapshotTaken(snapshot) in snapshot_events.iter() {
+        // the first world read back after the request, whoever asked for it
+        if !recorder.awaiting {
+            continue;
+        }
+        let every = recorder.settings.every;
+        let image = colors.to_image(&snapshot.channels, snapshot.scenario.world_size);
+        let Some(active) = &mut recorder.recorder else {
+            continue;
+        };
+        if let Err(e) = active.record(&image) {
+            error!("recording stopped: {e}");
+            recorder.finish();
+            continue;
+        }
+        recorder.next_capture = (snapshot.step / every + 1) * every;
+        recorder.awaiting = false;
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use super::{
+    LeniaInitialState, LeniaScenario, LeniaSeeding, LeniaWorldSize, ReseedWorld, StateFormat,
+};
+use crate::lenia_plugin::ActiveLeniaRule;
+use crate::scenario::Scenario;
+
+/// Event switching the running world to the rule of `scenario`, keeping the world's size and
+/// `StateFormat`. With `reinitialize` the world restarts from the scenario's initial condition
+/// and seed, otherwise it keeps its cells.
+pub struct ApplyScenario {
+    pub scenario: Scenario,
+    pub reinitialize: bool,
+}
+
+/// Runs before `Update`, so the initial state is in place when `reseed_world` restarts the world.
+pub(super) fn apply_scenarios(
+    mut commands: Commands,
+    mut apply_events: EventReader<ApplyScenario>,
+    mut scenario: ResMut<LeniaScenario>,
+    mut active_rule: ResMut<ActiveLeniaRule>,
+    world_size: Res<LeniaWorldSize>,
+    state_format: Res<StateFormat>,
+    mut reseed_events: EventWriter<ReseedWorld>,
+) {
+    let Some(event) = apply_events.iter().last() else {
+        return;
+    };
+    let mut next = Scenario {
+        world_size: world_size.0,
+        state_format: *state_format,
+        ..event.scenario.clone()
+    };
+    if !event.reinitialize {
+        next.initial_condition = scenario.initial_condition.clone();
+        next.seed = scenario.seed;
+    }
+    let board = match next.to_board() {
+        Ok(board) => board,
+        Err(error) => {
+            error!("scenario not applied: {error}");
+            return;
+        }
+    };
+    for diagnostic in board.diagnose() {
+        diagnostic.log();
+    }
+    *active_rule = ActiveLeniaRule::from_board(&board);
+
+    if event.reinitialize {
+        let initial_condition = board.get_initial_condition();
+        if initial_condition.is_generated_by_shader() {
+            commands.remove_resource::<LeniaInitialState>();
+            commands.insert_resource(LeniaSeeding::Shader);
+        } else {
+            commands.insert_resource(LeniaInitialState {
+                condition: initial_condition.clone(),
+                quantization: board.get_quantization(),
+                channels: board.generate_initial_cells(),
+            });
+            commands.insert_resource(LeniaSeeding::Uploaded);
+        }
+        reseed_events.send(ReseedWorld {
+            seed: Some(board.get_seed()),
+        });
+    }
+    // snapshots record the rule they were taken with
+    scenario.0 = next;
+}
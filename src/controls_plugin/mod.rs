@@ -0,0 +1,279 @@
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::compute_plugin::{
+    ApplyScenario, Brush, BrushMode, LeniaView, LeniaWorldSize, LeniaWorldSprite, PaintWorld,
+    ReseedWorld, SimulationClock, StepRate, ToggleRecording, MAX_STEPS_PER_FRAME,
+};
+use crate::scenario::Scenario;
+
+/// Keyboard and mouse control of the running simulation, configured by `LeniaControls`.
+pub struct LeniaControlsPlugin;
+
+impl Plugin for LeniaControlsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<SimulationClock>() {
+            error!("LeniaControlsPlugin needs LeniaComputePlugin, added first");
+            return;
+        }
+        // Kept if the app inserted its own bindings or rules.
+        app.init_resource::<LeniaControls>()
+            .init_resource::<LeniaRules>()
+            .add_event::<SelectRule>()
+            .add_system(simulation_keys)
+            .add_system(rule_keys)
+            .add_system(select_rule.after(rule_keys))
+            .add_system(view_keys)
+            .add_system(zoom_camera)
+            .add_system(pan_camera)
+            .add_system(paint_world);
+    }
+}
+
+/// Bindings of `LeniaControlsPlugin`. Every action fires on any of its keys.
+#[derive(Resource, Clone, Debug)]
+pub struct LeniaControls {
+    pub pause: Vec<KeyCode>,
+    pub step: Vec<KeyCode>, // single step, only while paused
+    pub reseed: Vec<KeyCode>,
+    pub speed_up: Vec<KeyCode>,
+    pub slow_down: Vec<KeyCode>,
+    pub rules: Vec<KeyCode>, // the n-th key selects the n-th of `LeniaRules`
+    pub field_view: Vec<KeyCode>, // cycles through the fields shown
+    pub side_by_side: Vec<KeyCode>, // toggles showing every field at once
+    pub record: Vec<KeyCode>, // starts or finishes a `LeniaRecorder` recording
+    pub pan: MouseButton,    // drag with it held to pan
+    pub paint: MouseButton,  // paint with `brush` while held
+    pub erase: Vec<KeyCode>, // held while painting to erase instead
+    pub brush: Brush,
+    pub zoom_factor: f32, // scale change per scrolled line
+    pub zoom_range: (f32, f32),
+}
+
+impl Default for LeniaControls {
+    fn default() -> Self {
+        Self {
+            pause: vec![KeyCode::Space],
+            step: vec![KeyCode::Period],
+            reseed: vec![KeyCode::R],
+            speed_up: vec![KeyCode::Equals, KeyCode::Plus, KeyCode::NumpadAdd],
+            slow_down: vec![KeyCode::Minus, KeyCode::NumpadSubtract],
+            rules: vec![
+                KeyCode::Key1,
+                KeyCode::Key2,
+                KeyCode::Key3,
+                KeyCode::Key4,
+                KeyCode::Key5,
+                KeyCode::Key6,
+                KeyCode::Key7,
+                KeyCode::Key8,
+                KeyCode::Key9,
+            ],
+            field_view: vec![KeyCode::V],
+            side_by_side: vec![KeyCode::B],
+            record: vec![KeyCode::C],
+            pan: MouseButton::Right,
+            paint: MouseButton::Left,
+            erase: vec![KeyCode::LShift, KeyCode::RShift],
+            brush: Brush::default(),
+            zoom_factor: 1.1,
+            zoom_range: (0.05, 20.0),
+        }
+    }
+}
+
+/// Predefined rules the number keys switch between.
+#[derive(Resource, Clone, Default, Deref, DerefMut)]
+pub struct LeniaRules(pub Vec<Scenario>);
+
+/// Event sent when a rule key is pressed, carrying the selected entry of `LeniaRules`. The
+/// running world switches to its rule, keeping its cells, size, seed and initial condition.
+pub struct SelectRule {
+    pub index: usize,
+    pub scenario: Scenario,
+}
+
+fn simulation_keys(
+    keys: Res<Input<KeyCode>>,
+    controls: Res<LeniaControls>,
+    mut clock: ResMut<SimulationClock>,
+    mut reseed_events: EventWriter<ReseedWorld>,
+    mut record_events: EventWriter<ToggleRecording>,
+) {
+    if keys.any_just_pressed(controls.pause.iter().copied()) {
+        clock.toggle_pause();
+    }
+    if keys.any_just_pressed(controls.step.iter().copied()) {
+        clock.request_step();
+    }
+    if keys.any_just_pressed(controls.reseed.iter().copied()) {
+        reseed_events.send(ReseedWorld { seed: None });
+    }
+    if keys.any_just_pressed(controls.record.iter().copied()) {
+        record_events.send(ToggleRecording);
+    }
+
+    let rate = if keys.any_just_pressed(controls.speed_up.iter().copied()) {
+        match clock.get_rate() {
+            StepRate::PerFrame(steps) => StepRate::PerFrame((steps * 2).min(MAX_STEPS_PER_FRAME)),
+            StepRate::PerSecond(steps) => StepRate::PerSecond(steps * 2.0),
+        }
+    } else if keys.any_just_pressed(controls.slow_down.iter().copied()) {
+        match clock.get_rate() {
+            StepRate::PerFrame(steps) => StepRate::PerFrame((steps / 2).max(1)),
+            StepRate::PerSecond(steps) => StepRate::PerSecond((steps / 2.0).max(1.0)),
+        }
+    } else {
+        return;
+    };
+    info!("step rate: {rate:?}");
+    clock.set_rate(rate);
+}
+
+fn rule_keys(
+    keys: Res<Input<KeyCode>>,
+    controls: Res<LeniaControls>,
+    rules: Res<LeniaRules>,
+    mut select_events: EventWriter<SelectRule>,
+) {
+    let pressed = controls
+        .rules
+        .iter()
+        .position(|key| keys.just_pressed(*key));
+    if let Some((index, scenario)) = pressed.and_then(|index| Some((index, rules.get(index)?))) {
+        select_events.send(SelectRule {
+            index,
+            scenario: scenario.clone(),
+        });
+    }
+}
+
+fn select_rule(
+    mut select_events: EventReader<SelectRule>,
+    mut apply_events: EventWriter<ApplyScenario>,
+) {
+    if let Some(SelectRule { index, scenario }) = select_events.iter().last() {
+        info!("switching to rule {}", index + 1);
+        apply_events.send(ApplyScenario {
+            scenario: scenario.clone(),
+            reinitialize: false,
+        });
+    }
+}
+
+fn view_keys(keys: Res<Input<KeyCode>>, controls: Res<LeniaControls>, mut view: ResMut<LeniaView>) {
+    if keys.any_just_pressed(controls.field_view.iter().copied()) {
+        view.field = view.field.next();
+        info!("showing {:?}", view.field);
+    }
+    if keys.any_just_pressed(controls.side_by_side.iter().copied()) {
+        view.side_by_side = !view.side_by_side;
+    }
+}
+
+/// Zooms towards the cursor, keeping the world point under it in place.
+fn zoom_camera(
+    mut wheel_events: EventReader<MouseWheel>,
+    controls: Res<LeniaControls>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let lines: f32 = wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 16.0,
+        })
+        .sum();
+    if lines == 0.0 {
+        return;
+    }
+
+    // offset of the cursor from the center of the window, the y axis points up in both
+    let cursor_offset = windows
+        .get_single()
+        .ok()
+        .and_then(|window| {
+            let center = Vec2::new(window.width(), window.height()) / 2.0;
+            Some(window.cursor_position()? - center)
+        })
+        .unwrap_or_default();
+
+    let (min_scale, max_scale) = controls.zoom_range;
+    for (mut transform, mut projection) in &mut cameras {
+        let scale =
+            (projection.scale * controls.zoom_factor.powf(-lines)).clamp(min_scale, max_scale);
+        transform.translation += (cursor_offset * (projection.scale - scale)).extend(0.0);
+        projection.scale = scale;
+    }
+}
+
+fn pan_camera(
+    mut motion_events: EventReader<MouseMotion>,
+    buttons: Res<Input<MouseButton>>,
+    controls: Res<LeniaControls>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+) {
+    let delta: Vec2 = motion_events.iter().map(|event| event.delta).sum();
+    if !buttons.pressed(controls.pan) || delta == Vec2::ZERO {
+        return;
+    }
+
+    // mouse motion points down, the world y axis up
+    for (mut transform, projection) in &mut cameras {
+        transform.translation += Vec3::new(-delta.x, delta.y, 0.0) * projection.scale;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paint_world(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    controls: Res<LeniaControls>,
+    world_size: Res<LeniaWorldSize>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    sprites: Query<(&Sprite, &GlobalTransform), With<LeniaWorldSprite>>,
+    mut paint_events: EventWriter<PaintWorld>,
+) {
+    if !buttons.pressed(controls.paint) {
+        return;
+    }
+    let Some(cursor) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let (Ok((camera, camera_transform)), Ok((sprite, sprite_transform))) =
+        (cameras.get_single(), sprites.get_single())
+    else {
+        return;
+    };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    // sprite space is centered with y up, cells count from the top left corner
+    let local = sprite_transform
+        .affine()
+        .inverse()
+        .transform_point3(ray.origin)
+        .truncate();
+    let size = Vec2::new(world_size.0 .0 as f32, world_size.0 .1 as f32);
+    let local = local / sprite.custom_size.unwrap_or(size);
+    let position = Vec2::new(local.x + 0.5, 0.5 - local.y) * size;
+    if position.cmplt(Vec2::ZERO).any() || position.cmpge(size).any() {
+        return;
+    }
+
+    let mut brush = controls.brush;
+    if keys.any_pressed(controls.erase.iter().copied()) {
+        brush.mode = BrushMode::Erase;
+    }
+    paint_events.send(PaintWorld { position, brush });
+}
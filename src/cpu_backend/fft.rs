@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+/// Circular convolution of the world with a fixed kernel through the frequency domain.
+///
+/// The kernel spectrum is computed once, so each step costs two 2D transforms and a
+/// pointwise multiply, independent of the kernel radius.
+pub struct FftConvolution {
+    width: usize,
+    height: usize,
+    kernel_spectrum: Vec<Complex<f32>>,
+    row_forward: Arc<dyn Fft<f32>>,
+    row_inverse: Arc<dyn Fft<f32>>,
+    column_forward: Arc<dyn Fft<f32>>,
+    column_inverse: Arc<dyn Fft<f32>>,
+}
+
+impl FftConvolution {
+    /// `kernel` is a row-major `(2 * kernel_radius + 1)²` weight grid centered on the site.
+    pub fn new(kernel: &[f32], kernel_radius: usize, space_resolution: (u32, u32)) -> Self {
+        let width = space_resolution.0 as usize;
+        let height = space_resolution.1 as usize;
+        let diameter = kernel_radius * 2 + 1;
+
+        let mut planner = FftPlanner::new();
+        let mut convolution = Self {
+            width,
+            height,
+            kernel_spectrum: vec![Complex::default(); width * height],
+            row_forward: planner.plan_fft_forward(width),
+            row_inverse: planner.plan_fft_inverse(width),
+            column_forward: planner.plan_fft_forward(height),
+            column_inverse: planner.plan_fft_inverse(height),
+        };
+
+        // The shader sums `kernel[r + d] * world[x + d]`, a correlation, so the kernel is
+        // mirrored around the origin to express it as a plain product of spectra.
+        for (index, weight) in kernel.iter().enumerate() {
+            let dx = (index % diameter) as i64 - kernel_radius as i64;
+            let dy = (index / diameter) as i64 - kernel_radius as i64;
+            let x = (-dx).rem_euclid(width as i64) as usize;
+            let y = (-dy).rem_euclid(height as i64) as usize;
+            convolution.kernel_spectrum[y * width + x].re += weight;
+        }
+
+        let mut spectrum = std::mem::take(&mut convolution.kernel_spectrum);
+        convolution.transform(&mut spectrum, false);
+        convolution.kernel_spectrum = spectrum;
+        convolution
+    }
+
+    /// Returns the unnormalized kernel sum at every site, row-major.
+    pub fn convolve(&self, cells: &[f32]) -> Vec<f32> {
+        let mut buffer: Vec<Complex<f32>> = cells.iter().map(|x| Complex::new(*x, 0.0)).collect();
+
+        self.transform(&mut buffer, false);
+        buffer
+            .iter_mut()
+            .zip(&self.kernel_spectrum)
+            .for_each(|(value, kernel)| *value *= kernel);
+        self.transform(&mut buffer, true);
+
+        let scale = 1.0 / (self.width * self.height) as f32;
+        buffer.iter().map(|value| value.re * scale).collect()
+    }
+
+    fn transform(&self, buffer: &mut [Complex<f32>], inverse: bool) {
+        let (row_fft, column_fft) = if inverse {
+            (&self.row_inverse, &self.column_inverse)
+        } else {
+            (&self.row_forward, &self.column_forward)
+        };
+
+        // rustfft processes every consecutive chunk of the transform length, i.e. every row.
+        row_fft.process(buffer);
+
+        let mut transposed = transpose(buffer, self.width, self.height);
+        column_fft.process(&mut transposed);
+        buffer.copy_from_slice(&transpose(&transposed, self.height, self.width));
+    }
+}
+
+fn transpose(buffer: &[Complex<f32>], width: usize, height: usize) -> Vec<Complex<f32>> {
+    let mut transposed = vec![Complex::default(); buffer.len()];
+    for y in 0..height {
+        for x in 0..width {
+            transposed[x * height + y] = buffer[y * width + x];
+        }
+    }
+    transposed
+}
@@ -0,0 +1,490 @@
+pub mod fft;
+
+use crate::compute_plugin::ResizeMode;
+use crate::error::LeniaError;
+use crate::lenia_plugin::lenia_rules::{check_world_size, quantize, KernelImage, LeniaBoard};
+
+use self::fft::FftConvolution;
+
+/// Kernel radius from which `ConvolutionMode::Auto` switches to the FFT path.
+pub const FFT_RADIUS_THRESHOLD: usize = 8;
+
+/// How `CpuLenia` computes the kernel potential.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvolutionMode {
+    Direct, // O(R²) loop per site, same as the shader
+    Fft,
+    Auto, // FFT for kernels of at least `FFT_RADIUS_THRESHOLD`, direct otherwise
+}
+
+/// Pure-Rust stepper mirroring the `update` entry point of `update_lenia.wgsl`.
+///
+/// Every channel is kept as a row-major `f32` grid with wrap-around edges, matching the GPU path
+/// with an `Rgba32Float` state.
+pub struct CpuLenia {
+    space_resolution: (u32, u32), // (width, height)
+    channels: Vec<Vec<f32>>,
+    kernels: Vec<CpuKernel>,
+    dt: f32,
+    quantization: Option<u32>, // discrete states per channel, as in `LeniaBoard`
+    convolution_mode: ConvolutionMode,
+}
+
+/// A rule kernel prepared for the CPU, the counterpart of a kernel texture layer, its growth
+/// lookup table and its `LeniaGPUKernel` entry.
+pub struct CpuKernel {
+    weights: Vec<f32>, // kernel weights, row-major, (2 * radius + 1)²
+    radius: usize,     // cells per kernel radius
+    area: f32,
+    growth_vector: Vec<f32>, // growth lookup table, same as the kernel's slice of the GPU growth array
+    source: usize,
+    target: usize,
+    weight: f32,
+    fft: Option<FftConvolution>,
+}
+
+impl CpuLenia {
+    pub fn new(lenia_board: &LeniaBoard) -> Self {
+        let rule = lenia_board.get_rule();
+        let kernels = lenia_board
+            .get_kernel_images()
+            .iter()
+            .zip(lenia_board.get_growth_vectors())
+            .zip(rule.get_kernels())
+            .map(|((kernel_image, growth_vector), kernel)| {
+                CpuKernel::new(kernel_image, growth_vector)
+                    .with_channels(kernel.get_source(), kernel.get_target())
+                    .with_weight(kernel.get_weight())
+            })
+            .collect();
+
+        let mut cpu_lenia = Self::from_parts(
+            rule.get_channels(),
+            kernels,
+            lenia_board.get_dt(),
+            lenia_board.get_space_resolution(),
+        );
+        cpu_lenia.quantization = lenia_board.get_quantization();
+        cpu_lenia.channels = lenia_board.generate_initial_cells();
+        cpu_lenia
+    }
+
+    pub fn from_parts(
+        channels: u32,
+        kernels: Vec<CpuKernel>,
+        dt: f32,
+        space_resolution: (u32, u32),
+    ) -> Self {
+        Self {
+            space_resolution,
+            channels: vec![
+                vec![0.0; (space_resolution.0 * space_resolution.1) as usize];
+                channels as usize
+            ],
+            kernels,
+            dt,
+            quantization: None,
+            convolution_mode: ConvolutionMode::Direct,
+        }
+        .with_convolution_mode(ConvolutionMode::Auto)
+    }
+
+    pub fn with_convolution_mode(mut self, convolution_mode: ConvolutionMode) -> Self {
+        self.convolution_mode = convolution_mode;
+        self.plan_convolutions();
+        self
+    }
+
+    fn plan_convolutions(&mut self) {
+        for kernel in &mut self.kernels {
+            let use_fft = match self.convolution_mode {
+                ConvolutionMode::Direct => false,
+                ConvolutionMode::Fft => true,
+                ConvolutionMode::Auto => kernel.radius >= FFT_RADIUS_THRESHOLD,
+            };
+            kernel.fft = use_fft.then(|| {
+                FftConvolution::new(&kernel.weights, kernel.radius, self.space_resolution)
+            });
+        }
+    }
+
+    pub fn with_quantization(self, levels: u32) -> Self {
+        Self {
+            quantization: Some(levels),
+            ..self
+        }
+    }
+
+    pub fn get_convolution_mode(&self) -> ConvolutionMode {
+        self.convolution_mode
+    }
+
+    /// Whether any kernel currently goes through the FFT path, resolving `ConvolutionMode::Auto`.
+    pub fn uses_fft(&self) -> bool {
+        self.kernels.iter().any(|kernel| kernel.fft.is_some())
+    }
+
+    pub fn get_space_resolution(&self) -> (u32, u32) {
+        self.space_resolution
+    }
+
+    pub fn get_channels(&self) -> u32 {
+        self.channels.len() as u32
+    }
+
+    pub fn get_kernels(&self) -> &[CpuKernel] {
+        &self.kernels
+    }
+
+    pub fn cells(&self, channel: u32) -> &[f32] {
+        &self.channels[channel as usize]
+    }
+
+    pub fn cells_mut(&mut self, channel: u32) -> &mut [f32] {
+        &mut self.channels[channel as usize]
+    }
+
+    /// Replaces a whole channel, `cells` must be row-major and match the space resolution.
+    pub fn set_cells(&mut self, channel: u32, cells: Vec<f32>) {
+        assert_eq!(
+            cells.len(),
+            self.channels[channel as usize].len(),
+            "cell count does not match the space resolution"
+        );
+        self.channels[channel as usize] = cells;
+    }
+
+    pub fn get_cell(&self, channel: u32, x: i32, y: i32) -> f32 {
+        self.channels[channel as usize][self.wrap(x, y)]
+    }
+
+    pub fn set_cell(&mut self, channel: u32, x: i32, y: i32, value: f32) {
+        let index = self.wrap(x, y);
+        self.channels[channel as usize][index] = value;
+    }
+
+    /// Changes the world size, carrying the state over like `resize_lenia.wgsl` does. Sizes
+    /// `LeniaBoard` would reject leave the world unchanged.
+    pub fn resize(
+        &mut self,
+        space_resolution: (u32, u32),
+        mode: ResizeMode,
+    ) -> Result<(), LeniaError> {
+        let kernel_diameter = self
+            .kernels
+            .iter()
+            .map(|kernel| kernel.radius as u32 * 2 + 1)
+            .max()
+            .unwrap_or(1);
+        check_world_size(space_resolution, kernel_diameter)?;
+
+        let (previous_width, previous_height) = (
+            self.space_resolution.0 as i32,
+            self.space_resolution.1 as i32,
+        );
+        let (width, height) = (space_resolution.0 as i32, space_resolution.1 as i32);
+        let sites = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+
+        for cells in &mut self.channels {
+            let previous = |x: i32, y: i32| cells[(y * previous_width + x) as usize];
+
+            *cells = match mode {
+                ResizeMode::Crop => sites
+                    .clone()
+                    .map(|(x, y)| {
+                        let source_x = x + (previous_width - width) / 2;
+                        let source_y = y + (previous_height - height) / 2;
+                        if (0..previous_width).contains(&source_x)
+                            && (0..previous_height).contains(&source_y)
+                        {
+                            previous(source_x, source_y)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect(),
+                ResizeMode::Resample => sites
+                    .clone()
+                    .map(|(x, y)| {
+                        let position_x =
+                            (x as f32 + 0.5) * previous_width as f32 / width as f32 - 0.5;
+                        let position_y =
+                            (y as f32 + 0.5) * previous_height as f32 / height as f32 - 0.5;
+                        let (tx, ty) = (
+                            position_x - position_x.floor(),
+                            position_y - position_y.floor(),
+                        );
+                        let low_x = (position_x.floor() as i32).clamp(0, previous_width - 1);
+                        let low_y = (position_y.floor() as i32).clamp(0, previous_height - 1);
+                        let high_x = (position_x.floor() as i32 + 1).clamp(0, previous_width - 1);
+                        let high_y = (position_y.floor() as i32 + 1).clamp(0, previous_height - 1);
+
+                        let top =
+                            previous(low_x, low_y) * (1.0 - tx) + previous(high_x, low_y) * tx;
+                        let bottom =
+                            previous(low_x, high_y) * (1.0 - tx) + previous(high_x, high_y) * tx;
+                        top * (1.0 - ty) + bottom * ty
+                    })
+                    .collect(),
+            };
+        }
+
+        self.space_resolution = space_resolution;
+        self.plan_convolutions();
+        Ok(())
+    }
+
+    /// Advances the world by one timestep.
+    pub fn step(&mut self) {
+        let potential_fields: Vec<Vec<f32>> = (0..self.kernels.len())
+            .map(|kernel_index| self.potential_field(kernel_index))
+            .collect();
+
+        for (channel, cells) in self.channels.iter_mut().enumerate() {
+            // Sum of the growth of every kernel targeting this channel, scaled by its weight.
+            let mut growth = vec![0.0; cells.len()];
+            for (kernel, potential_field) in self.kernels.iter().zip(&potential_fields) {
+                if kernel.target != channel {
+                    continue;
+                }
+                growth
+                    .iter_mut()
+                    .zip(potential_field)
+                    .for_each(|(growth, potential)| {
+                        *growth += kernel.weight * (2.0 * kernel.lookup_growth(*potential) - 1.0)
+                    });
+            }
+
+            for (current, growth) in cells.iter_mut().zip(growth) {
+                let next = (current.clamp(0.0, 1.0) + self.dt * growth).clamp(0.0, 1.0);
+                *current = match self.quantization {
+                    Some(levels) => quantize(next, levels),
+                    None => next,
+                };
+            }
+        }
+    }
+
+    /// Potential of a kernel at every site, row-major, using the selected convolution path.
+    pub fn potential_field(&self, kernel_index: usize) -> Vec<f32> {
+        let kernel = &self.kernels[kernel_index];
+        if let Some(fft) = &kernel.fft {
+            return fft
+                .convolve(&self.channels[kernel.source])
+                .into_iter()
+                .map(|sum| sum / kernel.area)
+                .collect();
+        }
+
+        let (width, height) = self.space_resolution;
+        (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| self.potential_at(kernel_index, x, y))
+            .collect()
+    }
+
+    /// Kernel convolution at a single site with the direct loop, normalized by the kernel area.
+    pub fn potential_at(&self, kernel_index: usize, x: i32, y: i32) -> f32 {
+        let kernel = &self.kernels[kernel_index];
+        let diameter = kernel.radius * 2 + 1;
+        let radius = kernel.radius as i32;
+        let cells = &self.channels[kernel.source];
+
+        let mut sum = 0.0;
+        for (index, weight) in kernel.weights.iter().enumerate() {
+            if *weight == 0.0 {
+                continue;
+            }
+            let dx = (index % diameter) as i32 - radius;
+            let dy = (index / diameter) as i32 - radius;
+            sum += cells[self.wrap(x + dx, y + dy)] * weight;
+        }
+
+        sum / kernel.area
+    }
+
+    fn wrap(&self, x: i32, y: i32) -> usize {
+        let (width, height) = self.space_resolution;
+        let x = x.rem_euclid(width as i32) as usize;
+        let y = y.rem_euclid(height as i32) as usize;
+        y * width as usize + x
+    }
+}
+
+impl CpuKernel {
+    /// Kernel reading from and growing channel 0 with weight 1.
+    pub fn new(kernel_image: &KernelImage, growth_vector: Vec<f32>) -> Self {
+        let kernel_buffer = kernel_image.image.to_rgba32f();
+
+        Self {
+            weights: kernel_buffer.pixels().map(|pixel| pixel[0]).collect(),
+            radius: (kernel_buffer.width() / 2) as usize,
+            area: kernel_image.area.x,
+            growth_vector,
+            source: 0,
+            target: 0,
+            weight: 1.0,
+            fft: None,
+        }
+    }
+
+    pub fn with_channels(self, source: u32, target: u32) -> Self {
+        Self {
+            source: source as usize,
+            target: target as usize,
+            ..self
+        }
+    }
+
+    pub fn with_weight(self, weight: f32) -> Self {
+        Self { weight, ..self }
+    }
+
+    /// Samples the growth lookup table the same way `calculate_growth` does on the GPU,
+    /// including its weighting of the two neighbouring entries.
+    pub fn lookup_growth(&self, potential: f32) -> f32 {
+        let last = self.growth_vector.len() - 1;
+        let float_index = potential * last as f32;
+        if float_index == 0.0 {
+            return self.growth_vector[0];
+        }
+        // Out of range indices are clamped, like robust buffer access does in the shader.
+        let left_index = (float_index.floor() as usize).min(last);
+        let right_index = (float_index.ceil() as usize).min(last);
+        let left_weight = float_index - float_index.floor();
+        let right_weight = 1.0 - left_weight;
+
+        self.growth_vector[left_index] * left_weight
+            + self.growth_vector[right_index] * right_weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+
+    // rounding differences double every step and jump past growth table entries after ~15
+    const STEPS: usize = 5;
+
+    fn board() -> LeniaBoard {
+        scenario().to_board().unwrap()
+    }
+
+    fn scenario() -> Scenario {
+        Scenario::from_toml(
+            r#"
+            world_size = [64, 48]
+            radius = 10
+            dt = 0.1
+            growth_resolution = 100
+            seed = 7
+
+            [[kernels]]
+            beta = [1.0, 0.5]
+            core = { type = "PolynomialCore", alpha = 4.0 }
+            growth = { type = "GaussianGrowth", mu = 0.15, sigma = 0.015 }
+
+            [initial_condition]
+            type = "Blobs"
+            count = 4
+            radius = 12.0
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn run(mode: ConvolutionMode) -> CpuLenia {
+        let mut cpu_lenia = CpuLenia::new(&board()).with_convolution_mode(mode);
+        for _ in 0..STEPS {
+            cpu_lenia.step();
+        }
+        cpu_lenia
+    }
+
+    fn max_difference(a: &[f32], b: &[f32]) -> f32 {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn direct_and_fft_convolutions_agree() {
+        let (direct, fft) = (run(ConvolutionMode::Direct), run(ConvolutionMode::Fft));
+        assert!(fft.uses_fft() && !direct.uses_fft());
+        assert!(direct.cells(0).iter().any(|cell| *cell > 0.0));
+
+        let potentials = max_difference(&direct.potential_field(0), &fft.potential_field(0));
+        assert!(potentials < 1e-5, "potentials differ by {potentials}");
+        let cells = max_difference(direct.cells(0), fft.cells(0));
+        assert!(cells < 1e-4, "cells differ by {cells}");
+    }
+
+    #[test]
+    fn quantized_boards_start_quantized() {
+        let mut scenario = Scenario::load("assets/scenarios/discrete_lenia.lenia.toml").unwrap();
+        scenario.world_size = (64, 64);
+        scenario.seed = Some(7);
+        let cpu_lenia = CpuLenia::new(&scenario.to_board().unwrap());
+        let levels = 12.0;
+        assert!(cpu_lenia
+            .cells(0)
+            .iter()
+            .all(|cell| (cell * levels - (cell * levels).round()).abs() < 1e-5));
+        assert!(cpu_lenia.cells(0).iter().any(|cell| *cell > 0.0));
+    }
+
+    #[test]
+    fn runs_from_the_same_seed_are_identical() {
+        let (first, second) = (run(ConvolutionMode::Auto), run(ConvolutionMode::Auto));
+        let bits = |cpu_lenia: &CpuLenia| -> Vec<u32> {
+            cpu_lenia
+                .cells(0)
+                .iter()
+                .map(|cell| cell.to_bits())
+                .collect()
+        };
+        assert_eq!(bits(&first), bits(&second));
+    }
+
+    #[test]
+    fn resizing_below_the_kernel_diameter_is_rejected() {
+        let mut cpu_lenia = CpuLenia::new(&board());
+        assert!(cpu_lenia.resize((0, 48), ResizeMode::Crop).is_err());
+        assert!(cpu_lenia.resize((64, 20), ResizeMode::Resample).is_err());
+        assert_eq!(cpu_lenia.get_space_resolution(), (64, 48));
+
+        cpu_lenia.resize((32, 24), ResizeMode::Crop).unwrap();
+        assert_eq!(cpu_lenia.get_space_resolution(), (32, 24));
+        assert_eq!(cpu_lenia.cells(0).len(), 32 * 24);
+    }
+
+    #[test]
+    fn growth_is_scaled_by_the_weight_but_not_normalized() {
+        let step = |weight: f32| {
+            let mut scenario = scenario();
+            scenario.kernels[0].weight = weight;
+            let mut cpu_lenia = CpuLenia::new(&scenario.to_board().unwrap());
+            let initial = cpu_lenia.cells(0).to_vec();
+            cpu_lenia.step();
+            (initial, cpu_lenia.cells(0).to_vec())
+        };
+        let (initial, full) = step(1.0);
+        let (_, half) = step(0.5);
+        // cells clamped to [0, 1] by the full step say nothing about its growth
+        let unclamped = initial
+            .iter()
+            .zip(full.iter().zip(&half))
+            .filter(|(_, (full, _))| **full > 0.0 && **full < 1.0)
+            .map(|(initial, (full, half))| (full - initial, half - initial))
+            .collect::<Vec<_>>();
+        assert!(unclamped.iter().any(|(full, _)| full.abs() > 1e-3));
+        for (full, half) in unclamped {
+            assert!(
+                (full - 2.0 * half).abs() < 1e-5,
+                "{full} is not twice {half}"
+            );
+        }
+    }
+}
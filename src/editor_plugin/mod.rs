@@ -0,0 +1,419 @@
+use std::mem::discriminant;
+
+use bevy::{input::mouse::MouseWheel, prelude::*, window::PrimaryWindow};
+use bevy_egui::{
+    egui::{
+        self,
+        plot::{Line, Plot, PlotPoints},
+        Color32,
+    },
+    EguiContexts, EguiPlugin, EguiSet,
+};
+
+use crate::compute_plugin::{ApplyScenario, LeniaScenario, SimulationClock};
+use crate::lenia_plugin::{
+    diagnostics::Severity,
+    lenia_rules::{LeniaRule, MappingSpec, MappingType},
+};
+use crate::scenario::{KernelSpec, Scenario};
+
+/// Points at which the kernel profile and growth curve are plotted.
+const PLOT_SAMPLES: u32 = 201;
+
+/// Largest kernel radius offered by the radius slider, further bounded by the world size.
+const MAX_RADIUS: u32 = 128;
+
+const CORE_TYPES: [MappingType; 4] = [
+    MappingType::GaussianCore { alpha: 4.0 },
+    MappingType::PolynomialCore { alpha: 4.0 },
+    MappingType::StepCore,
+    MappingType::StaircaseCore,
+];
+
+const GROWTH_TYPES: [MappingType; 3] = [
+    MappingType::GaussianGrowth {
+        mu: 0.15,
+        sigma: 0.015,
+    },
+    MappingType::PolynomialGrowth {
+        mu: 0.15,
+        sigma: 0.015,
+        alpha: 4.0,
+    },
+    MappingType::StepGrowth {
+        mu: 0.15,
+        sigma: 0.015,
+    },
+];
+
+/// Panel editing the rule of the running world, toggled by `LeniaEditor::toggle`. Edits are
+/// made on a draft of `LeniaScenario` and pushed to the world with `ApplyScenario`.
+pub struct LeniaEditorPlugin;
+
+impl Plugin for LeniaEditorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<SimulationClock>() {
+            error!("LeniaEditorPlugin needs LeniaComputePlugin, added first");
+            return;
+        }
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        // Kept if the app inserted its own editor state.
+        app.init_resource::<LeniaEditor>()
+            .add_system(
+                absorb_input
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(EguiSet::ProcessInput),
+            )
+            .add_system(toggle_editor)
+            .add_system(editor_window.after(toggle_editor));
+    }
+}
+
+/// State of the `LeniaEditorPlugin` panel.
+#[derive(Resource, Clone, Debug)]
+pub struct LeniaEditor {
+    pub open: bool,
+    pub toggle: Vec<KeyCode>,    // opens or closes the panel
+    pub kernel: usize,           // index of the kernel being edited
+    pub reinitialize: bool,      // restart the world from its initial condition on apply
+    pub draft: Option<Scenario>, // replaced by `LeniaScenario` whenever the running rule changes
+}
+
+impl Default for LeniaEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            toggle: vec![KeyCode::E],
+            kernel: 0,
+            reinitialize: false,
+            draft: None,
+        }
+    }
+}
+
+/// Keeps clicks, scrolls and key presses on the panel from reaching the world controls.
+fn absorb_input(
+    mut contexts: EguiContexts,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse_wheel: ResMut<Events<MouseWheel>>,
+) {
+    let Some(ctx) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| contexts.try_ctx_for_window_mut(window))
+    else {
+        return;
+    };
+    if ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+        mouse_buttons.reset_all();
+        mouse_wheel.clear();
+    }
+    if ctx.wants_keyboard_input() {
+        keys.reset_all();
+    }
+}
+
+fn toggle_editor(keys: Res<Input<KeyCode>>, mut editor: ResMut<LeniaEditor>) {
+    if keys.any_just_pressed(editor.toggle.iter().copied()) {
+        editor.open = !editor.open;
+    }
+}
+
+fn editor_window(
+    mut contexts: EguiContexts,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut editor: ResMut<LeniaEditor>,
+    scenario: Res<LeniaScenario>,
+    mut apply_events: EventWriter<ApplyScenario>,
+) {
+    if scenario.is_changed() || editor.draft.is_none() {
+        editor.draft = Some(scenario.0.clone());
+    }
+    if !editor.open {
+        return;
+    }
+    let Some(ctx) = windows
+        .get_single()
+        .ok()
+        .and_then(|window| contexts.try_ctx_for_window_mut(window))
+    else {
+        return;
+    };
+
+    let LeniaEditor {
+        open,
+        kernel,
+        reinitialize,
+        draft: Some(draft),
+        ..
+    } = &mut *editor
+    else {
+        return;
+    };
+    egui::Window::new("Rule")
+        .open(open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            board_parameters(ui, draft);
+            ui.separator();
+
+            *kernel = (*kernel).min(draft.kernels.len().saturating_sub(1));
+            egui::ComboBox::from_label("kernel")
+                .selected_text(kernel_name(draft, *kernel))
+                .show_ui(ui, |ui| {
+                    for index in 0..draft.kernels.len() {
+                        ui.selectable_value(kernel, index, kernel_name(draft, index));
+                    }
+                });
+            if let Some(spec) = draft.kernels.get_mut(*kernel) {
+                kernel_parameters(ui, spec);
+                ui.separator();
+                kernel_plots(ui, spec);
+            }
+            ui.separator();
+
+            let problems = rule_problems(draft);
+            for (severity, message) in &problems {
+                let color = match severity {
+                    Severity::Warning => Color32::YELLOW,
+                    Severity::Error => Color32::RED,
+                };
+                ui.colored_label(color, message);
+            }
+            let valid = problems
+                .iter()
+                .all(|(severity, _)| *severity != Severity::Error);
+
+            ui.checkbox(reinitialize, "restart the world");
+            ui.horizontal(|ui| {
+                if ui.add_enabled(valid, egui::Button::new("Apply")).clicked() {
+                    apply_events.send(ApplyScenario {
+                        scenario: draft.clone(),
+                        reinitialize: *reinitialize,
+                    });
+                }
+                if ui.button("Revert").clicked() {
+                    *draft = scenario.0.clone();
+                }
+            });
+        });
+}
+
+fn board_parameters(ui: &mut egui::Ui, draft: &mut Scenario) {
+    ui.add(
+        egui::Slider::new(&mut draft.dt, 0.001..=1.0)
+            .logarithmic(true)
+            .text("dt"),
+    );
+    // the largest kernel still has to fit in the world
+    let max_radius_scale = draft
+        .kernels
+        .iter()
+        .fold(1.0, |scale: f32, kernel| scale.max(kernel.radius_scale));
+    let (width, height) = draft.world_size;
+    let fitting = ((width.min(height) - 1) as f32 / 2.0 / max_radius_scale) as u32;
+    ui.add(egui::Slider::new(&mut draft.radius, 1..=fitting.clamp(1, MAX_RADIUS)).text("radius"));
+}
+
+fn kernel_name(draft: &Scenario, index: usize) -> String {
+    match draft.kernels.get(index) {
+        Some(kernel) if draft.channels > 1 => {
+            format!("{index}: {} → {}", kernel.source, kernel.target)
+        }
+        _ => index.to_string(),
+    }
+}
+
+fn kernel_parameters(ui: &mut egui::Ui, spec: &mut KernelSpec) {
+    ui.label("peaks");
+    for (index, peak) in spec.beta.iter_mut().enumerate() {
+        ui.add(egui::Slider::new(peak, 0.0..=1.0).text(format!("β{index}")));
+    }
+    ui.horizontal(|ui| {
+        if ui.button("add peak").clicked() {
+            spec.beta.push(1.0);
+        }
+        if ui
+            .add_enabled(spec.beta.len() > 1, egui::Button::new("remove peak"))
+            .clicked()
+        {
+            spec.beta.pop();
+        }
+    });
+    mapping_parameters(ui, "core", &mut spec.core, &CORE_TYPES);
+    mapping_parameters(ui, "growth", &mut spec.growth, &GROWTH_TYPES);
+}
+
+fn mapping_parameters(
+    ui: &mut egui::Ui,
+    label: &str,
+    spec: &mut MappingSpec,
+    choices: &[MappingType],
+) {
+    let MappingSpec::Type(ty) = spec else {
+        ui.label(format!(
+            "{label}: tables and expressions are edited in the scenario file"
+        ));
+        return;
+    };
+    let mut selected = *ty;
+    egui::ComboBox::from_label(label)
+        .selected_text(type_name(ty))
+        .show_ui(ui, |ui| {
+            for choice in choices {
+                ui.selectable_value(
+                    &mut selected,
+                    carry_parameters(*choice, ty),
+                    type_name(choice),
+                );
+            }
+        });
+    *ty = selected;
+
+    match ty {
+        MappingType::GaussianCore { alpha } | MappingType::PolynomialCore { alpha } => {
+            ui.add(egui::Slider::new(alpha, 0.5..=16.0).text("alpha"));
+        }
+        MappingType::StepCore | MappingType::StaircaseCore => {}
+        MappingType::GaussianGrowth { mu, sigma } | MappingType::StepGrowth { mu, sigma } => {
+            growth_sliders(ui, mu, sigma);
+        }
+        MappingType::PolynomialGrowth { mu, sigma, alpha } => {
+            growth_sliders(ui, mu, sigma);
+            ui.add(egui::Slider::new(alpha, 0.5..=16.0).text("alpha"));
+        }
+    }
+}
+
+fn growth_sliders(ui: &mut egui::Ui, mu: &mut f32, sigma: &mut f32) {
+    ui.add(egui::Slider::new(mu, 0.0..=1.0).text("mu"));
+    ui.add(
+        egui::Slider::new(sigma, 0.001..=0.5)
+            .logarithmic(true)
+            .text("sigma"),
+    );
+}
+
+fn type_name(ty: &MappingType) -> &'static str {
+    match ty {
+        MappingType::GaussianCore { .. } => "gaussian",
+        MappingType::PolynomialCore { .. } => "polynomial",
+        MappingType::StepCore => "step",
+        MappingType::StaircaseCore => "staircase",
+        MappingType::GaussianGrowth { .. } => "gaussian",
+        MappingType::PolynomialGrowth { .. } => "polynomial",
+        MappingType::StepGrowth { .. } => "step",
+    }
+}
+
+/// `choice` with the parameters of `current` it shares, so switching types keeps `mu`, `sigma`
+/// and `alpha`.
+fn carry_parameters(choice: MappingType, current: &MappingType) -> MappingType {
+    if discriminant(&choice) == discriminant(current) {
+        return *current;
+    }
+    let alpha = match *current {
+        MappingType::GaussianCore { alpha }
+        | MappingType::PolynomialCore { alpha }
+        | MappingType::PolynomialGrowth { alpha, .. } => Some(alpha),
+        _ => None,
+    };
+    let mu_sigma = match *current {
+        MappingType::GaussianGrowth { mu, sigma }
+        | MappingType::PolynomialGrowth { mu, sigma, .. }
+        | MappingType::StepGrowth { mu, sigma } => Some((mu, sigma)),
+        _ => None,
+    };
+    match choice {
+        MappingType::GaussianCore { alpha: default } => MappingType::GaussianCore {
+            alpha: alpha.unwrap_or(default),
+        },
+        MappingType::PolynomialCore { alpha: default } => MappingType::PolynomialCore {
+            alpha: alpha.unwrap_or(default),
+        },
+        MappingType::GaussianGrowth { mu, sigma } => {
+            let (mu, sigma) = mu_sigma.unwrap_or((mu, sigma));
+            MappingType::GaussianGrowth { mu, sigma }
+        }
+        MappingType::PolynomialGrowth {
+            mu,
+            sigma,
+            alpha: default,
+        } => {
+            let (mu, sigma) = mu_sigma.unwrap_or((mu, sigma));
+            MappingType::PolynomialGrowth {
+                mu,
+                sigma,
+                alpha: alpha.unwrap_or(default),
+            }
+        }
+        MappingType::StepGrowth { mu, sigma } => {
+            let (mu, sigma) = mu_sigma.unwrap_or((mu, sigma));
+            MappingType::StepGrowth { mu, sigma }
+        }
+        MappingType::StepCore | MappingType::StaircaseCore => choice,
+    }
+}
+
+/// Radial profile of the kernel and the growth applied at each potential, `2 * growth - 1`.
+fn kernel_plots(ui: &mut egui::Ui, spec: &KernelSpec) {
+    // invalid kernels are reported with the other problems of the rule
+    let Ok(kernel) = spec.to_kernel() else {
+        return;
+    };
+    let shell = kernel.get_kernel_shell();
+    let growth = kernel.get_growth_mapping();
+    let samples = |f: &dyn Fn(f32) -> f32| -> PlotPoints {
+        (0..PLOT_SAMPLES)
+            .map(|index| {
+                let x = index as f32 / (PLOT_SAMPLES - 1) as f32;
+                [x as f64, f(x) as f64]
+            })
+            .collect()
+    };
+
+    ui.label("kernel profile");
+    Plot::new("kernel_profile")
+        .height(100.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .include_y(0.0)
+        .include_y(1.0)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(samples(&|r| shell.profile(r))));
+        });
+    ui.label("growth");
+    Plot::new("growth_curve")
+        .height(100.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .include_y(-1.0)
+        .include_y(1.0)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(samples(&|u| 2.0 * growth(u) - 1.0)));
+        });
+}
+
+/// Diagnostics of the draft rule, or the error keeping it from being built.
+fn rule_problems(draft: &Scenario) -> Vec<(Severity, String)> {
+    let rule = draft
+        .kernels
+        .iter()
+        .map(KernelSpec::to_kernel)
+        .collect::<Result<_, _>>()
+        .and_then(|kernels| Ok(LeniaRule::try_multi_channel(draft.channels, kernels)?));
+    match rule {
+        Ok(rule) => rule
+            .validate()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.to_string()))
+            .collect(),
+        Err(error) => vec![(Severity::Error, error.to_string())],
+    }
+}
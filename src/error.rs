@@ -0,0 +1,80 @@
+use std::{fmt, io};
+
+use crate::pattern::rle::RleError;
+use crate::scenario::{expression::ExpressionError, ScenarioError};
+
+/// Why a board could not be configured or its files read, returned by the `try_` constructors
+/// of the rule types and by `LeniaRenderPlugin`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeniaError {
+    InvalidDimensions(String),
+    InvalidParameter(String),
+    EmptyBeta,             // kernel shell without peaks
+    GrowthResolution(u32), // below 2
+    Render(String),        // no render device to run the world on
+    Io(String),
+    Image(String),
+    Parse(String),
+}
+
+impl fmt::Display for LeniaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeniaError::InvalidDimensions(error) => write!(f, "invalid dimensions: {error}"),
+            LeniaError::InvalidParameter(error) => write!(f, "invalid parameter: {error}"),
+            LeniaError::EmptyBeta => write!(f, "a kernel shell needs at least one peak in `beta`"),
+            LeniaError::GrowthResolution(resolution) => {
+                write!(f, "growth resolution {resolution} is below 2")
+            }
+            LeniaError::Render(error) => write!(f, "cannot render the world: {error}"),
+            LeniaError::Io(error) => write!(f, "{error}"),
+            LeniaError::Image(error) => write!(f, "image error: {error}"),
+            LeniaError::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LeniaError {}
+
+impl From<io::Error> for LeniaError {
+    fn from(error: io::Error) -> Self {
+        LeniaError::Io(error.to_string())
+    }
+}
+
+impl From<image::ImageError> for LeniaError {
+    fn from(error: image::ImageError) -> Self {
+        match error {
+            image::ImageError::IoError(error) => LeniaError::Io(error.to_string()),
+            error => LeniaError::Image(error.to_string()),
+        }
+    }
+}
+
+impl From<ScenarioError> for LeniaError {
+    fn from(error: ScenarioError) -> Self {
+        match error {
+            ScenarioError::Board(error) => error,
+            ScenarioError::Io(error) => LeniaError::Io(error),
+            ScenarioError::Image(error) => LeniaError::Image(error),
+            ScenarioError::Invalid(error) => LeniaError::InvalidParameter(error),
+            error => LeniaError::Parse(error.to_string()),
+        }
+    }
+}
+
+impl From<RleError> for LeniaError {
+    fn from(error: RleError) -> Self {
+        match error {
+            RleError::Board(error) => error,
+            RleError::Io(error) => LeniaError::Io(error),
+            error => LeniaError::Parse(error.to_string()),
+        }
+    }
+}
+
+impl From<ExpressionError> for LeniaError {
+    fn from(error: ExpressionError) -> Self {
+        LeniaError::Parse(error.to_string())
+    }
+}
@@ -0,0 +1,287 @@
+pub mod noise;
+
+use std::path::Path;
+
+use bevy::math::{Vec2, Vec3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::error::LeniaError;
+use crate::pattern::PatternStamp;
+
+/// How the world is filled before the first step. Every condition can be generated on the CPU,
+/// `Landscape` and `Perlin` are generated by the `init` shader on the GPU and the others are
+/// uploaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitialCondition {
+    Landscape {
+        scale: f32,  // feature size of the large noise layer, in cells
+        detail: f32, // feature size of the detailed layer it is multiplied with
+    },
+    Perlin {
+        octaves: u32,
+        scale: f32, // feature size of the first octave, in cells
+    },
+    Uniform {
+        low: f32,
+        high: f32,
+    },
+    Blobs {
+        count: u32,
+        radius: f32, // in cells
+        region: f32, // side of the central area holding the blob centers, relative to the world
+    },
+    Empty,
+    Image(InitialImage),
+    Patterns(Vec<PatternStamp>), // stamped into an empty world
+}
+
+impl Default for InitialCondition {
+    fn default() -> Self {
+        InitialCondition::Landscape {
+            scale: 100.0,
+            detail: 40.0,
+        }
+    }
+}
+
+/// An image stretched over the world. Single channel worlds take its luminance, the others one
+/// color component per channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitialImage {
+    path: String,
+    size: (u32, u32),
+    pixels: Vec<[f32; 3]>, // row-major RGB in [0, 1]
+}
+
+impl InitialImage {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, image::ImageError> {
+        let image = image::open(path.as_ref())?.to_rgb32f();
+        Ok(Self {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            size: image.dimensions(),
+            pixels: image.pixels().map(|pixel| pixel.0).collect(),
+        })
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Nearest pixel to the cell at `(x, y)` of a world of `space_resolution`.
+    fn sample(&self, x: u32, y: u32, space_resolution: (u32, u32)) -> [f32; 3] {
+        let image_x = (x as u64 * self.size.0 as u64 / space_resolution.0 as u64) as u32;
+        let image_y = (y as u64 * self.size.1 as u64 / space_resolution.1 as u64) as u32;
+        self.pixels[(image_y * self.size.0 + image_x) as usize]
+    }
+}
+
+impl InitialCondition {
+    /// Rejects parameters `generate` cannot produce a world from.
+    pub fn validate(&self) -> Result<(), LeniaError> {
+        let invalid = |message: String| Err(LeniaError::InvalidParameter(message));
+        match *self {
+            InitialCondition::Perlin { scale, .. } if !(scale.is_finite() && scale > 0.0) => {
+                invalid(format!("noise scale {scale} must be positive"))
+            }
+            InitialCondition::Landscape { scale, detail }
+                if !(scale.is_finite() && scale > 0.0 && detail.is_finite() && detail > 0.0) =>
+            {
+                invalid(format!(
+                    "landscape scale {scale} and detail {detail} must be positive"
+                ))
+            }
+            InitialCondition::Uniform { low, high }
+                if !(low.is_finite() && high.is_finite() && low <= high) =>
+            {
+                invalid(format!("uniform range {low}..={high} is empty"))
+            }
+            InitialCondition::Blobs { radius, region, .. }
+                if !(radius.is_finite() && radius > 0.0 && region > 0.0 && region <= 1.0) =>
+            {
+                invalid(format!(
+                    "blob radius {radius} must be positive and region {region} in (0, 1]"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the `init` shader generates the condition on the GPU instead of it being uploaded.
+    pub fn is_generated_by_shader(&self) -> bool {
+        matches!(
+            self,
+            InitialCondition::Landscape { .. } | InitialCondition::Perlin { .. }
+        )
+    }
+
+    /// Cells of every channel in row-major order. The same seed always gives the same world,
+    /// and matches the `init` shader run with `noise_offset(seed)`.
+    pub fn generate(
+        &self,
+        space_resolution: (u32, u32),
+        channels: u32,
+        seed: u64,
+    ) -> Vec<Vec<f32>> {
+        let (width, height) = space_resolution;
+        let mut cells = vec![vec![0.0; (width * height) as usize]; channels as usize];
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        match self {
+            InitialCondition::Landscape { scale, detail } => {
+                for (channel, cells) in cells.iter_mut().enumerate() {
+                    let offset = channel_noise_offset(seed, channel);
+                    for (index, cell) in cells.iter_mut().enumerate() {
+                        let position =
+                            Vec2::new((index as u32 % width) as f32, (index as u32 / width) as f32);
+                        *cell = noise::landscape(position, offset, *scale, *detail).clamp(0.0, 1.0);
+                    }
+                }
+            }
+            InitialCondition::Perlin { octaves, scale } => {
+                for (channel, cells) in cells.iter_mut().enumerate() {
+                    let offset = channel_noise_offset(seed, channel);
+                    for (index, cell) in cells.iter_mut().enumerate() {
+                        let position =
+                            Vec2::new((index as u32 % width) as f32, (index as u32 / width) as f32);
+                        *cell = noise::fbm(position, offset, *octaves, *scale).clamp(0.0, 1.0);
+                    }
+                }
+            }
+            InitialCondition::Uniform { low, high } => {
+                for cell in cells.iter_mut().flatten() {
+                    *cell = rng.gen_range(*low..=*high);
+                }
+            }
+            InitialCondition::Blobs {
+                count,
+                radius,
+                region,
+            } => {
+                let reach = radius.ceil() as i32;
+                for _ in 0..*count {
+                    let center = Vec2::new(
+                        width as f32 * (0.5 + region * (rng.gen::<f32>() - 0.5)),
+                        height as f32 * (0.5 + region * (rng.gen::<f32>() - 0.5)),
+                    );
+                    for dy in -reach..=reach {
+                        for dx in -reach..=reach {
+                            if Vec2::new(dx as f32, dy as f32).length() > *radius {
+                                continue;
+                            }
+                            let x = (center.x as i32 + dx).rem_euclid(width as i32);
+                            let y = (center.y as i32 + dy).rem_euclid(height as i32);
+                            let index = (y * width as i32 + x) as usize;
+                            for cells in &mut cells {
+                                cells[index] = rng.gen();
+                            }
+                        }
+                    }
+                }
+            }
+            InitialCondition::Empty => {}
+            InitialCondition::Image(image) => {
+                for (channel, cells) in cells.iter_mut().enumerate() {
+                    for (index, cell) in cells.iter_mut().enumerate() {
+                        let (x, y) = (index as u32 % width, index as u32 / width);
+                        let [r, g, b] = image.sample(x, y, space_resolution);
+                        *cell = match (channels, channel) {
+                            (1, _) => 0.299 * r + 0.587 * g + 0.114 * b,
+                            (_, channel) => [r, g, b][channel],
+                        };
+                    }
+                }
+            }
+            InitialCondition::Patterns(stamps) => {
+                for stamp in stamps {
+                    stamp.pattern.stamp(
+                        &mut cells[stamp.channel as usize],
+                        space_resolution,
+                        stamp.position,
+                    );
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// Offset into the noise volume the `init` shader is run with for `seed`, in noise lattice
+/// units. x and y span the 289 cell period of the noise, so seeds give different worlds rather
+/// than nearby slices of the same one.
+pub fn noise_offset(seed: u64) -> Vec3 {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let z = rng.gen::<f32>();
+    Vec3::new(rng.gen::<f32>() * 289.0, rng.gen::<f32>() * 289.0, z)
+}
+
+// every channel samples a different slice of the noise volume
+fn channel_noise_offset(seed: u64, channel: usize) -> Vec3 {
+    noise_offset(seed) + Vec3::Z * channel as f32 * 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_parameters_that_cannot_generate_a_world() {
+        for condition in [
+            InitialCondition::Uniform {
+                low: 1.0,
+                high: 0.0,
+            },
+            InitialCondition::Uniform {
+                low: f32::NAN,
+                high: 1.0,
+            },
+            InitialCondition::Perlin {
+                octaves: 2,
+                scale: 0.0,
+            },
+            InitialCondition::Blobs {
+                count: 3,
+                radius: f32::NAN,
+                region: 0.5,
+            },
+            InitialCondition::Blobs {
+                count: 3,
+                radius: 4.0,
+                region: 1.5,
+            },
+        ] {
+            assert!(condition.validate().is_err(), "{condition:?}");
+        }
+        assert!(InitialCondition::default().validate().is_ok());
+    }
+
+    #[test]
+    fn uniform_cells_stay_in_range() {
+        let condition = InitialCondition::Uniform {
+            low: 0.25,
+            high: 0.25,
+        };
+        let cells = condition.generate((8, 8), 2, 1);
+        assert!(cells.iter().flatten().all(|cell| *cell == 0.25));
+    }
+
+    #[test]
+    fn seeds_move_the_noise_in_every_axis() {
+        assert_eq!(noise_offset(3), noise_offset(3));
+        let (first, second) = (noise_offset(3), noise_offset(4));
+        assert!(first.cmpne(second).all());
+        assert!(first.x < 289.0 && first.y < 289.0);
+
+        let condition = InitialCondition::Perlin {
+            octaves: 2,
+            scale: 16.0,
+        };
+        assert_ne!(
+            condition.generate((32, 32), 1, 3),
+            condition.generate((32, 32), 1, 4)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! CPU port of the Perlin noise in `init_lenia.wgsl`, so noise seeded worlds can be previewed
+//! without the GPU. Float `%` truncates in both WGSL and Rust.
+
+use bevy::math::{Vec2, Vec3, Vec4};
+
+/// Product of a large and a detailed layer of Perlin noise, mostly empty with scattered
+/// patches. `offset` selects the region of the noise volume, in lattice units.
+pub fn landscape(position: Vec2, offset: Vec3, scale: f32, detail: f32) -> f32 {
+    perlin_noise3((position / scale).extend(0.0) + offset)
+        * perlin_noise3((position / detail).extend(0.0) + offset)
+}
+
+/// Fractional brownian motion over `octaves` of Perlin noise, each half the size and weight of
+/// the previous one, normalized to about [-1, 1]. `offset` selects the region of the noise
+/// volume, in lattice units.
+pub fn fbm(position: Vec2, offset: Vec3, octaves: u32, scale: f32) -> f32 {
+    let (mut value, mut total) = (0.0, 0.0);
+    let (mut amplitude, mut zoom) = (1.0, scale);
+    for _ in 0..octaves {
+        value += amplitude * perlin_noise3((position / zoom).extend(0.0) + offset);
+        total += amplitude;
+        amplitude *= 0.5;
+        zoom *= 0.5;
+    }
+    value / f32::max(total, 1.0)
+}
+
+fn permute4(x: Vec4) -> Vec4 {
+    ((x * 34.0 + 1.0) * x) % Vec4::splat(289.0)
+}
+
+fn taylor_inv_sqrt4(r: Vec4) -> Vec4 {
+    Vec4::splat(1.792_842_9) - 0.853_734_73 * r
+}
+
+fn fade3(t: Vec3) -> Vec3 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn fract4(x: Vec4) -> Vec4 {
+    x - x.floor()
+}
+
+// `step(edge, x)` of WGSL
+fn step4(edge: Vec4, x: Vec4) -> Vec4 {
+    Vec4::select(x.cmpge(edge), Vec4::ONE, Vec4::ZERO)
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
+
+/// Gradients of the four corners hashed to `ixy`, as (x, y, z) components.
+fn gradients(ixy: Vec4, sign: f32) -> (Vec4, Vec4, Vec4) {
+    let gx = ixy / 7.0;
+    let gy = fract4(gx.floor() / 7.0) - 0.5;
+    let gx = fract4(gx);
+    let gz = Vec4::splat(0.5) - gx.abs() - gy.abs();
+    let sz = step4(gz, Vec4::ZERO);
+    let gx = gx + sign * sz * (step4(Vec4::ZERO, gx) - 0.5);
+    let gy = gy + sign * sz * (step4(Vec4::ZERO, gy) - 0.5);
+    (gx, gy, gz)
+}
+
+pub fn perlin_noise3(p: Vec3) -> f32 {
+    let pi0 = p.floor() % Vec3::splat(289.0);
+    let pi1 = (p.floor() + Vec3::ONE) % Vec3::splat(289.0);
+    let pf0 = p - p.floor();
+    let pf1 = pf0 - Vec3::ONE;
+    let ix = Vec4::new(pi0.x, pi1.x, pi0.x, pi1.x);
+    let iy = Vec4::new(pi0.y, pi0.y, pi1.y, pi1.y);
+
+    let ixy = permute4(permute4(ix) + iy);
+    let ixy0 = permute4(ixy + Vec4::splat(pi0.z));
+    let ixy1 = permute4(ixy + Vec4::splat(pi1.z));
+
+    let (gx0, gy0, gz0) = gradients(ixy0, 1.0);
+    let (gx1, gy1, gz1) = gradients(ixy1, -1.0);
+    let corner = |gx: Vec4, gy: Vec4, gz: Vec4, i: usize| Vec3::new(gx[i], gy[i], gz[i]);
+
+    let (g000, g100, g010, g110) = (
+        corner(gx0, gy0, gz0, 0),
+        corner(gx0, gy0, gz0, 1),
+        corner(gx0, gy0, gz0, 2),
+        corner(gx0, gy0, gz0, 3),
+    );
+    let (g001, g101, g011, g111) = (
+        corner(gx1, gy1, gz1, 0),
+        corner(gx1, gy1, gz1, 1),
+        corner(gx1, gy1, gz1, 2),
+        corner(gx1, gy1, gz1, 3),
+    );
+
+    let norm0 = taylor_inv_sqrt4(Vec4::new(
+        g000.dot(g000),
+        g010.dot(g010),
+        g100.dot(g100),
+        g110.dot(g110),
+    ));
+    let norm1 = taylor_inv_sqrt4(Vec4::new(
+        g001.dot(g001),
+        g011.dot(g011),
+        g101.dot(g101),
+        g111.dot(g111),
+    ));
+
+    let n000 = (g000 * norm0.x).dot(pf0);
+    let n100 = (g100 * norm0.z).dot(Vec3::new(pf1.x, pf0.y, pf0.z));
+    let n010 = (g010 * norm0.y).dot(Vec3::new(pf0.x, pf1.y, pf0.z));
+    let n110 = (g110 * norm0.w).dot(Vec3::new(pf1.x, pf1.y, pf0.z));
+    let n001 = (g001 * norm1.x).dot(Vec3::new(pf0.x, pf0.y, pf1.z));
+    let n101 = (g101 * norm1.z).dot(Vec3::new(pf1.x, pf0.y, pf1.z));
+    let n011 = (g011 * norm1.y).dot(Vec3::new(pf0.x, pf1.y, pf1.z));
+    let n111 = (g111 * norm1.w).dot(pf1);
+
+    let fade = fade3(pf0);
+    let n_z = Vec4::new(n000, n100, n010, n110).lerp(Vec4::new(n001, n101, n011, n111), fade.z);
+    let n_yz = Vec2::new(mix(n_z.x, n_z.z, fade.y), mix(n_z.y, n_z.w, fade.y));
+    2.2 * mix(n_yz.x, n_yz.y, fade.x)
+}
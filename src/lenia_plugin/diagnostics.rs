@@ -0,0 +1,354 @@
+use std::fmt;
+
+use bevy::prelude::{error, warn};
+
+use super::lenia_rules::{LeniaBoard, LeniaKernel, LeniaRule, Mapping, MappingSpec, MappingType};
+
+/// Points at which kernel cores and growth mappings are sampled over [0, 1].
+const SAMPLES: u32 = 1001;
+
+/// Timesteps above this let a cell cross the whole [0, 1] range in one step.
+const MAX_STABLE_DT: f32 = 1.0;
+
+/// Timesteps below this barely move the world between frames.
+const MIN_USEFUL_DT: f32 = 0.001;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning, // the world runs but is likely to die out, explode or stall
+    Error,   // the world computes NaNs or divides by zero
+}
+
+/// A problem found by `LeniaRule::validate` or `LeniaBoard::diagnose`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kernel: Option<usize>, // index of the offending rule kernel
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn warning(kernel: Option<usize>, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            kernel,
+            message,
+        }
+    }
+
+    fn error(kernel: Option<usize>, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            kernel,
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Logs the diagnostic as a warning or an error.
+    pub fn log(&self) {
+        match self.severity {
+            Severity::Warning => warn!("{self}"),
+            Severity::Error => error!("{self}"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kernel {
+            Some(kernel) => write!(f, "kernel {kernel}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl LeniaRule {
+    /// Checks the mappings of every kernel for parameters and samples that would make the
+    /// world compute NaNs, or never grow at all. An empty list means nothing was found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for (index, kernel) in self.get_kernels().iter().enumerate() {
+            validate_kernel(index, kernel, &mut diagnostics);
+        }
+        diagnostics
+    }
+}
+
+impl LeniaBoard {
+    /// `LeniaRule::validate`, plus the checks that depend on the discretization of the board:
+    /// rendered kernel areas, growth lookup tables and the timestep.
+    pub fn diagnose(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.get_rule().validate();
+        let kernels = self.get_rule().get_kernels();
+
+        for (index, kernel_image) in self.get_kernel_images().iter().enumerate() {
+            // zero areas are already rejected by `LeniaBoard::try_new`
+            let area = kernel_image.area.x;
+            if !area.is_finite() {
+                diagnostics.push(Diagnostic::error(
+                    Some(index),
+                    format!("kernel area is {area}"),
+                ));
+            }
+            let radius = self.get_dx() as f32 * kernels[index].get_radius_scale();
+            if radius < 1.0 {
+                diagnostics.push(Diagnostic::warning(
+                    Some(index),
+                    format!("kernel radius of {radius} cells is smaller than a cell"),
+                ));
+            }
+        }
+
+        for (index, growth_vector) in self.get_growth_vectors().iter().enumerate() {
+            if let Some(position) = growth_vector.iter().position(|value| !value.is_finite()) {
+                diagnostics.push(Diagnostic::error(
+                    Some(index),
+                    format!(
+                        "growth lookup table has {} at entry {position}",
+                        growth_vector[position]
+                    ),
+                ));
+            }
+        }
+
+        let dt = self.get_dt();
+        if dt > MAX_STABLE_DT {
+            diagnostics.push(Diagnostic::warning(
+                None,
+                format!(
+                    "timestep {dt} is above {MAX_STABLE_DT}, cells overshoot and the world is \
+                     likely unstable"
+                ),
+            ));
+        } else if dt < MIN_USEFUL_DT {
+            diagnostics.push(Diagnostic::warning(
+                None,
+                format!("timestep {dt} is below {MIN_USEFUL_DT}, the world will barely change"),
+            ));
+        }
+        diagnostics
+    }
+}
+
+fn validate_kernel(index: usize, kernel: &LeniaKernel, diagnostics: &mut Vec<Diagnostic>) {
+    let kernel_shell = kernel.get_kernel_shell();
+    let (core, growth) = (kernel_shell.get_kernel_core(), kernel.get_growth_mapping());
+    let kernel = Some(index);
+
+    for mapping in [core, growth] {
+        if let Some(MappingSpec::Type(ty)) = mapping.get_spec() {
+            if let Some(message) = invalid_type(ty) {
+                diagnostics.push(Diagnostic::error(kernel, message));
+            }
+        }
+    }
+
+    let core_samples = sample(core);
+    if let Some((x, value)) = first_non_finite(&core_samples) {
+        diagnostics.push(Diagnostic::error(
+            kernel,
+            format!("kernel core is {value} at x = {x}"),
+        ));
+    } else if core_samples.iter().any(|(_, value)| *value < 0.0) {
+        diagnostics.push(Diagnostic::warning(
+            kernel,
+            "kernel core has negative values, potentials may leave [0, 1]".to_string(),
+        ));
+    }
+    let peak = kernel_shell
+        .get_beta()
+        .iter()
+        .fold(0.0, |peak: f32, beta| peak.max(*beta));
+    if peak <= 0.0 || core_samples.iter().all(|(_, value)| *value <= 0.0) {
+        diagnostics.push(Diagnostic::error(
+            kernel,
+            "kernel has zero area, the potential divides by zero".to_string(),
+        ));
+    }
+
+    // the update grows cells by `2 * growth - 1`
+    let growth_samples = sample(growth);
+    if let Some((x, value)) = first_non_finite(&growth_samples) {
+        diagnostics.push(Diagnostic::error(
+            kernel,
+            format!("growth mapping is {value} at potential {x}"),
+        ));
+        return;
+    }
+    if growth_samples.iter().all(|(_, value)| *value <= 0.5) {
+        diagnostics.push(Diagnostic::warning(
+            kernel,
+            "growth never goes positive, every world dies out".to_string(),
+        ));
+    } else if growth_samples[0].1 > 0.5 {
+        diagnostics.push(Diagnostic::warning(
+            kernel,
+            "growth is positive at potential 0, empty space fills up".to_string(),
+        ));
+    }
+    if growth_samples
+        .iter()
+        .any(|(_, value)| !(0.0..=1.0).contains(value))
+    {
+        diagnostics.push(Diagnostic::warning(
+            kernel,
+            "growth mapping leaves [0, 1], growth is not bounded by ±1".to_string(),
+        ));
+    }
+}
+
+/// Parameters the mapping functions divide by or exponentiate into NaN and infinities.
+fn invalid_type(ty: &MappingType) -> Option<String> {
+    match *ty {
+        MappingType::GaussianCore { alpha } if !(alpha.is_finite() && alpha > 0.0) => {
+            Some(format!(
+                "gaussian core alpha {alpha} must be positive, exp(alpha - alpha / 0) is not \
+                 finite at x = 0 and 1"
+            ))
+        }
+        MappingType::PolynomialCore { alpha } if !(alpha.is_finite() && alpha >= 0.0) => Some(
+            format!("polynomial core alpha {alpha} must be non-negative"),
+        ),
+        MappingType::GaussianGrowth { mu, sigma }
+        | MappingType::PolynomialGrowth { mu, sigma, .. }
+        | MappingType::StepGrowth { mu, sigma }
+            if !(mu.is_finite() && sigma.is_finite() && sigma > 0.0) =>
+        {
+            Some(format!(
+                "growth mu {mu} and sigma {sigma} must be finite, with a positive sigma"
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn sample(mapping: &Mapping) -> Vec<(f32, f32)> {
+    (0..SAMPLES)
+        .map(|index| {
+            let x = index as f32 / (SAMPLES - 1) as f32;
+            (x, mapping(x))
+        })
+        .collect()
+}
+
+fn first_non_finite(samples: &[(f32, f32)]) -> Option<(f32, f32)> {
+    samples
+        .iter()
+        .find(|(_, value)| !value.is_finite())
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lenia_plugin::lenia_rules::KernelShell;
+
+    fn rule(core: MappingType, growth: MappingType) -> LeniaRule {
+        LeniaRule::new(
+            KernelShell::new(vec![1.0], Mapping::from_type(core)),
+            Mapping::from_type(growth),
+        )
+    }
+
+    fn orbium() -> LeniaRule {
+        rule(
+            MappingType::PolynomialCore { alpha: 4.0 },
+            MappingType::PolynomialGrowth {
+                mu: 0.15,
+                sigma: 0.015,
+                alpha: 4.0,
+            },
+        )
+    }
+
+    fn board(dt: f32) -> LeniaBoard {
+        LeniaBoard::try_new(orbium(), (64, 64), 13, dt, 100).unwrap()
+    }
+
+    fn has_warning(diagnostics: &[Diagnostic], text: &str) -> bool {
+        diagnostics.iter().any(|diagnostic| {
+            diagnostic.severity == Severity::Warning && diagnostic.message.contains(text)
+        })
+    }
+
+    #[test]
+    fn zero_gaussian_alpha_is_an_error() {
+        let diagnostics = rule(
+            MappingType::GaussianCore { alpha: 0.0 },
+            MappingType::GaussianGrowth {
+                mu: 0.15,
+                sigma: 0.015,
+            },
+        )
+        .validate();
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.is_error() && diagnostic.kernel == Some(0)));
+    }
+
+    #[test]
+    fn warns_about_growth_that_never_goes_positive() {
+        // a Gaussian over a potential it never reaches stays near 0
+        let diagnostics = rule(
+            MappingType::PolynomialCore { alpha: 4.0 },
+            MappingType::GaussianGrowth {
+                mu: 2.0,
+                sigma: 0.1,
+            },
+        )
+        .validate();
+        assert!(has_warning(&diagnostics, "never goes positive"));
+    }
+
+    #[test]
+    fn warns_about_growth_that_fills_empty_space() {
+        let diagnostics = rule(
+            MappingType::PolynomialCore { alpha: 4.0 },
+            MappingType::GaussianGrowth {
+                mu: 0.0,
+                sigma: 0.1,
+            },
+        )
+        .validate();
+        assert!(has_warning(&diagnostics, "empty space fills up"));
+    }
+
+    #[test]
+    fn warns_about_timesteps_out_of_range() {
+        assert!(has_warning(&board(1.5).diagnose(), "timestep"));
+        assert!(has_warning(&board(0.0005).diagnose(), "timestep"));
+        assert!(!has_warning(&board(0.1).diagnose(), "timestep"));
+    }
+
+    #[test]
+    fn example_rules_have_no_errors() {
+        let lenia_game = LeniaBoard::try_new(
+            LeniaRule::new(
+                KernelShell::new(
+                    vec![0.5, 2.0 / 3.0, 1.0],
+                    Mapping::from_type(MappingType::GaussianCore { alpha: 4.0 }),
+                ),
+                Mapping::from_type(MappingType::GaussianGrowth {
+                    mu: 0.25,
+                    sigma: 0.03,
+                }),
+            ),
+            (128, 128),
+            26,
+            0.1,
+            100,
+        )
+        .unwrap();
+        for board in [board(0.1), lenia_game] {
+            let diagnostics = board.diagnose();
+            assert!(
+                !diagnostics.iter().any(Diagnostic::is_error),
+                "{diagnostics:?}"
+            );
+        }
+    }
+}
@@ -1,14 +1,17 @@
 // #![allow(unused)]
 
-use std::{sync::Arc, usize};
+use std::{ops::Deref, path::Path, sync::Arc};
 
+use crate::compute_plugin::StateFormat;
+use crate::error::LeniaError;
+use crate::initial_condition::{noise_offset, InitialCondition};
 use crate::lenia_plugin::params;
+use crate::pattern::PatternStamp;
+use crate::scenario::expression::{Expression, ExpressionError};
 // use crate::*;
 
-use bevy::{
-    math::Vec2,
-    prelude::{Deref, Vec4},
-};
+use bevy::{math::Vec2, prelude::Vec4};
+use serde::{Deserialize, Serialize};
 
 pub struct LeniaBoard {
     lenia_rule: LeniaRule,
@@ -16,11 +19,68 @@ pub struct LeniaBoard {
     dx: u32, // The site distance, in which the kernel is applied over a site distance of 2
     dt: f32, // timestep
     growth_resolution: u32,
-    kernel_image: KernelImage, // Kernel rendered as an image file
+    kernel_radius: u32, // radius in cells of the largest kernel, shared by all kernel images
+    kernel_images: Vec<KernelImage>, // Kernels rendered as image files, one per rule kernel
+    initial_condition: InitialCondition,
+    seed: u64,                 // seeds every random source of the run, random by default
+    state_format: StateFormat, // texture format of the world state
+    quantization: Option<u32>, // number of discrete states (P), continuous if `None`
 }
+
+/// Maximum number of channels a world can have. Channels are stored in the R, G and B
+/// components of the state texture, alpha is kept at 1, so rules with 4 or more channels are
+/// rejected rather than silently dropping one.
+pub const MAX_CHANNELS: u32 = 3;
+
+/// Rejects empty worlds and worlds smaller than the kernel, which would overlap with itself.
+pub fn check_world_size(
+    space_resolution: (u32, u32),
+    kernel_diameter: u32,
+) -> Result<(), LeniaError> {
+    let (width, height) = space_resolution;
+    if width == 0 || height == 0 {
+        return Err(LeniaError::InvalidDimensions(format!(
+            "world size {width}x{height} is empty"
+        )));
+    }
+    if kernel_diameter > width || kernel_diameter > height {
+        return Err(LeniaError::InvalidDimensions(format!(
+            "kernel diameter {kernel_diameter} is larger than the {width}x{height} world, the \
+             kernel would overlap with itself"
+        )));
+    }
+    Ok(())
+}
+
+/// Rounds a cell to the nearest of `levels` discrete states, as the shaders do.
+pub fn quantize(value: f32, levels: u32) -> f32 {
+    (value * levels as f32).round() / levels as f32
+}
+
+/// Rounds every cell of `channels` if the board is quantized.
+pub fn quantize_cells(channels: &mut [Vec<f32>], levels: Option<u32>) {
+    if let Some(levels) = levels {
+        for cell in channels.iter_mut().flatten() {
+            *cell = quantize(*cell, levels);
+        }
+    }
+}
+
 pub struct LeniaRule {
+    channels: u32,
+    kernels: Vec<LeniaKernel>,
+}
+
+/// One kernel of a (multi-channel) rule: the potential is taken over the `source` channel,
+/// and its growth is applied to the `target` channel with weight `h`. The growths of the
+/// kernels sharing a target are summed, `Σ h_i G_i`, so their weights should add up to 1.
+pub struct LeniaKernel {
     kernel_shell: KernelShell,
     growth_mapping: Mapping,
+    source: u32,
+    target: u32,
+    weight: f32, // h, factor of the growth in the sum over the kernels of the target
+    radius_scale: f32, // kernel radius relative to the board radius
 }
 
 pub struct KernelShell {
@@ -28,48 +88,120 @@ pub struct KernelShell {
     kernel_core: Mapping, // kernel core K_C : [0, 1] → [0, 1]
 }
 
-#[derive(Deref)]
-pub struct Mapping(Arc<dyn Fn(f32) -> f32 + Send + Sync>);
+#[derive(Clone)]
+pub struct Mapping {
+    function: Arc<dyn Fn(f32) -> f32 + Send + Sync>,
+    spec: Option<MappingSpec>, // serializable description, `None` for arbitrary closures
+}
 
 #[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum MappingType {
     GaussianCore { alpha: f32 },
     PolynomialCore { alpha: f32 },
     StepCore,
+    StaircaseCore, // step core with a half-height inner step, as in Game of Life
     GaussianGrowth { mu: f32, sigma: f32 },
     PolynomialGrowth { mu: f32, sigma: f32, alpha: f32 },
     StepGrowth { mu: f32, sigma: f32 },
 }
 
+/// A mapping that can be written to a scenario file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MappingSpec {
+    Type(MappingType),
+    Table { table: Vec<f32> }, // samples over [0, 1], linearly interpolated
+    Expression { expression: String }, // function of `x`, see `Expression`
+}
+
 #[allow(dead_code)]
 impl Mapping {
     pub fn new(f: Arc<dyn Fn(f32) -> f32 + Send + Sync>) -> Self {
-        Self(f)
+        Self {
+            function: f,
+            spec: None,
+        }
+    }
+
+    pub fn from_spec(spec: MappingSpec) -> Result<Self, ExpressionError> {
+        let function: Arc<dyn Fn(f32) -> f32 + Send + Sync> = match &spec {
+            MappingSpec::Type(ty) => return Ok(Self::from_type(*ty)),
+            MappingSpec::Table { table } => {
+                if table.is_empty() {
+                    return Err(ExpressionError("table mapping has no samples".to_string()));
+                }
+                let table = table.clone();
+                Arc::new(move |x: f32| {
+                    let position = x.clamp(0.0, 1.0) * (table.len() - 1) as f32;
+                    let index = position.floor() as usize;
+                    let next = (index + 1).min(table.len() - 1);
+                    let t = position - index as f32;
+                    table[index] * (1.0 - t) + table[next] * t
+                })
+            }
+            MappingSpec::Expression { expression } => {
+                let expression = Expression::parse(expression)?;
+                Arc::new(move |x: f32| expression.eval(x))
+            }
+        };
+        Ok(Self {
+            function,
+            spec: Some(spec),
+        })
     }
 
     pub fn from_type(ty: MappingType) -> Self {
         let func = move |x: f32| match ty {
             MappingType::GaussianCore { alpha } => (alpha - alpha / (4.0 * x * (1.0 - x))).exp(),
             MappingType::PolynomialCore { alpha } => (4.0 * x * (1.0 - x)).powf(alpha),
-            MappingType::StepCore => (0.25 <= x && x <= 0.75) as u8 as f32,
+            MappingType::StepCore => (0.25..=0.75).contains(&x) as u8 as f32,
+            MappingType::StaircaseCore => {
+                (0.25..=0.75).contains(&x) as u8 as f32 + (x < 0.25) as u8 as f32 * 0.5
+            }
             MappingType::GaussianGrowth { mu, sigma } => {
                 (-((x - mu).powi(2)) / (2.0 * sigma.powi(2))).exp()
             }
             MappingType::PolynomialGrowth { mu, sigma, alpha } => {
                 (((x - mu).abs() <= 3.0 * sigma) as u8 as f32)
                     * (1.0 - (x - mu).powi(2) / (9.0 * sigma.powi(2))).powf(alpha)
-            }   
-            MappingType::StepGrowth { mu, sigma } => {
-                ((x - mu).abs() <= sigma) as u8 as f32
             }
+            MappingType::StepGrowth { mu, sigma } => ((x - mu).abs() <= sigma) as u8 as f32,
         };
-        Self(Arc::new(func))
+        Self {
+            function: Arc::new(func),
+            spec: Some(MappingSpec::Type(ty)),
+        }
+    }
+
+    pub fn get_spec(&self) -> Option<&MappingSpec> {
+        self.spec.as_ref()
+    }
+
+    /// The serializable description, or `samples` points of the function over [0, 1] if it was
+    /// built from a closure. At least both ends are sampled.
+    pub fn to_spec(&self, samples: u32) -> MappingSpec {
+        let samples = samples.max(2);
+        self.spec.clone().unwrap_or_else(|| MappingSpec::Table {
+            table: (0..samples)
+                .map(|index| (self.function)(index as f32 / (samples - 1) as f32))
+                .collect(),
+        })
+    }
+}
+
+impl Deref for Mapping {
+    type Target = Arc<dyn Fn(f32) -> f32 + Send + Sync>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.function
     }
 }
 
 impl From<Arc<dyn Fn(f32) -> f32 + Send + Sync>> for Mapping {
     fn from(value: Arc<dyn Fn(f32) -> f32 + Send + Sync>) -> Self {
-        Self(value)
+        Self::new(value)
     }
 }
 
@@ -94,16 +226,7 @@ impl KernelImage {
             if normal_dist > 1.0 {
                 *pixel = image::Rgba::<f32>([0.0, 0.0, 0.0, 1.0]);
             } else {
-                let beta_size = shell.beta.len();
-                let kr = normal_dist * beta_size as f32;
-                let index = kr.floor() as usize;
-                let value;
-                if index < beta_size {
-                    value = shell.beta[kr.floor() as usize] * (shell.kernel_core)(kr.fract());
-                } else {
-                    value = 0.0;
-                }
-
+                let value = shell.profile(normal_dist);
                 *pixel = image::Rgba::<f32>([value, value, value, 1.0]);
                 area += Vec4::splat(value);
             }
@@ -116,12 +239,13 @@ impl KernelImage {
     }
 
     /// Saves kernel as an image file, not useful for calculation purposes.
-    pub fn save_image(&self, path: &str) -> image::ImageResult<()> {
-        self.image.clone().into_rgba16().save(path)
+    pub fn save_image(&self, path: impl AsRef<Path>) -> Result<(), LeniaError> {
+        Ok(self.image.clone().into_rgba16().save(path)?)
     }
 }
 
 impl LeniaBoard {
+    /// Panics on an invalid configuration, see `try_new`.
     pub fn new(
         lenia_rule: LeniaRule,
         space_resolution: (u32, u32),
@@ -129,54 +253,449 @@ impl LeniaBoard {
         dt: f32,
         growth_resolution: u32,
     ) -> Self {
-        if r * 2 + 1 > space_resolution.0 || r * 2 + 1 > space_resolution.1 {
-            panic!("diameter is larger than `space_resolution`, kernel will overlap with itself")
+        Self::try_new(lenia_rule, space_resolution, r, dt, growth_resolution)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(
+        lenia_rule: LeniaRule,
+        space_resolution: (u32, u32),
+        r: u32, // cells per kernel radius
+        dt: f32,
+        growth_resolution: u32,
+    ) -> Result<Self, LeniaError> {
+        if r == 0 {
+            return Err(LeniaError::InvalidParameter(
+                "kernel radius must be at least one cell".to_string(),
+            ));
         }
-        let kernel_image = KernelImage::new(&lenia_rule.kernel_shell, r, 1.0);
-        Self {
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(LeniaError::InvalidParameter(format!(
+                "timestep {dt} is not positive"
+            )));
+        }
+        if growth_resolution < 2 {
+            return Err(LeniaError::GrowthResolution(growth_resolution));
+        }
+
+        let max_radius_scale = lenia_rule
+            .kernels
+            .iter()
+            .map(|kernel| kernel.radius_scale)
+            .fold(0.0, f32::max);
+        let kernel_radius = (r as f32 * max_radius_scale).ceil() as u32;
+        check_world_size(space_resolution, kernel_radius * 2 + 1)?;
+
+        // Every kernel is drawn on a canvas of the largest radius and zoomed to its own radius,
+        // so they can be stacked into one texture array.
+        let kernel_images: Vec<KernelImage> = lenia_rule
+            .kernels
+            .iter()
+            .map(|kernel| {
+                let zoom = r as f32 * kernel.radius_scale / kernel_radius as f32;
+                KernelImage::new(&kernel.kernel_shell, kernel_radius, zoom)
+            })
+            .collect();
+        // the potential is normalized by the kernel area
+        if let Some(index) = kernel_images
+            .iter()
+            .position(|kernel_image| kernel_image.area.x.is_nan() || kernel_image.area.x <= 0.0)
+        {
+            return Err(LeniaError::InvalidParameter(format!(
+                "kernel {index} has no positive weight"
+            )));
+        }
+
+        Ok(Self {
             lenia_rule,
             space_resolution,
             dx: r,
             dt,
             growth_resolution,
-            kernel_image,
+            kernel_radius,
+            kernel_images,
+            initial_condition: InitialCondition::default(),
+            seed: rand::random(),
+            state_format: StateFormat::default(),
+            quantization: None,
+        })
+    }
+
+    /// Replaces the random seed, making the run reproducible.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn with_state_format(self, state_format: StateFormat) -> Self {
+        self.try_with_state_format(state_format)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_with_state_format(self, state_format: StateFormat) -> Result<Self, LeniaError> {
+        if self.lenia_rule.channels > state_format.channels() {
+            return Err(LeniaError::InvalidParameter(format!(
+                "state format {state_format:?} cannot hold every channel of the rule"
+            )));
         }
+        Ok(Self {
+            state_format,
+            ..self
+        })
+    }
+
+    pub fn get_state_format(&self) -> StateFormat {
+        self.state_format
+    }
+
+    /// Rounds the state to `levels` discrete values after every step, as in discrete-state Lenia.
+    pub fn with_quantization(self, levels: u32) -> Self {
+        self.try_with_quantization(levels)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_with_quantization(self, levels: u32) -> Result<Self, LeniaError> {
+        if levels == 0 {
+            return Err(LeniaError::InvalidParameter(
+                "quantization needs at least one level".to_string(),
+            ));
+        }
+        Ok(Self {
+            quantization: Some(levels),
+            ..self
+        })
+    }
+
+    pub fn get_quantization(&self) -> Option<u32> {
+        self.quantization
+    }
+
+    pub fn with_initial_condition(self, initial_condition: InitialCondition) -> Self {
+        self.try_with_initial_condition(initial_condition)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_with_initial_condition(
+        self,
+        initial_condition: InitialCondition,
+    ) -> Result<Self, LeniaError> {
+        initial_condition.validate()?;
+        if let InitialCondition::Patterns(stamps) = &initial_condition {
+            if stamps
+                .iter()
+                .any(|stamp| stamp.channel >= self.lenia_rule.channels)
+            {
+                return Err(pattern_channel_error());
+            }
+        }
+        Ok(Self {
+            initial_condition,
+            ..self
+        })
+    }
+
+    pub fn get_initial_condition(&self) -> &InitialCondition {
+        &self.initial_condition
+    }
+
+    /// Adds a pattern to the initial world; a board with patterns starts from an empty world.
+    pub fn with_pattern(self, stamp: PatternStamp) -> Self {
+        self.try_with_pattern(stamp)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_with_pattern(mut self, stamp: PatternStamp) -> Result<Self, LeniaError> {
+        if stamp.channel >= self.lenia_rule.channels {
+            return Err(pattern_channel_error());
+        }
+        match &mut self.initial_condition {
+            InitialCondition::Patterns(stamps) => stamps.push(stamp),
+            initial_condition => *initial_condition = InitialCondition::Patterns(vec![stamp]),
+        }
+        Ok(self)
+    }
+
+    /// Initial cells of every channel computed on the CPU, the ones the GPU starts from as well.
+    /// Quantized boards start from quantized cells.
+    pub fn generate_initial_cells(&self) -> Vec<Vec<f32>> {
+        let mut channels = self.initial_condition.generate(
+            self.space_resolution,
+            self.lenia_rule.channels,
+            self.seed,
+        );
+        quantize_cells(&mut channels, self.quantization);
+        channels
     }
 
     pub fn generate_params(&self) -> params::LeniaGPUParams {
-        params::LeniaGPUParams::new(
-            rand::random::<f32>(),
-            self.kernel_image.area.x,
-            (self.dx * 2 + 1) as f32,
+        let params = params::LeniaGPUParams::new(
+            noise_offset(self.seed),
+            (self.kernel_radius * 2 + 1) as f32,
             self.dt,
             self.growth_resolution,
-        )
+            self.lenia_rule.channels,
+            self.lenia_rule.kernels.len() as u32,
+            self.quantization.unwrap_or(0),
+        );
+        match self.initial_condition {
+            InitialCondition::Landscape { scale, detail } => params.with_landscape(scale, detail),
+            InitialCondition::Perlin { octaves, scale } => params.with_noise(octaves, scale),
+            _ => params,
+        }
     }
 
-    pub fn get_kernel_image(&self) -> KernelImage {
-        self.kernel_image.clone()
+    pub fn generate_kernel_params(&self) -> Vec<params::LeniaGPUKernel> {
+        self.lenia_rule
+            .kernels
+            .iter()
+            .zip(&self.kernel_images)
+            .map(|(kernel, kernel_image)| {
+                params::LeniaGPUKernel::new(
+                    kernel.source,
+                    kernel.target,
+                    kernel.weight,
+                    kernel_image.area.x,
+                )
+            })
+            .collect()
     }
 
-    pub fn get_growth_vector(&self) -> Vec<f32> {
-        (0..self.growth_resolution)
-            .map(|index| {
-                (self.lenia_rule.growth_mapping)(index as f32 / (self.growth_resolution - 1) as f32)
+    pub fn get_rule(&self) -> &LeniaRule {
+        &self.lenia_rule
+    }
+
+    pub fn get_space_resolution(&self) -> (u32, u32) {
+        self.space_resolution
+    }
+
+    pub fn get_dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn get_growth_resolution(&self) -> u32 {
+        self.growth_resolution
+    }
+
+    /// Cells per kernel radius, before the per-kernel radius scale.
+    pub fn get_dx(&self) -> u32 {
+        self.dx
+    }
+
+    /// Radius in cells of the kernel images, i.e. of the largest kernel.
+    pub fn get_kernel_radius(&self) -> u32 {
+        self.kernel_radius
+    }
+
+    pub fn get_kernel_images(&self) -> Vec<KernelImage> {
+        self.kernel_images.clone()
+    }
+
+    /// Growth lookup tables, one per rule kernel, in the same order as the kernels.
+    pub fn get_growth_vectors(&self) -> Vec<Vec<f32>> {
+        self.lenia_rule
+            .kernels
+            .iter()
+            .map(|kernel| {
+                (0..self.growth_resolution)
+                    .map(|index| {
+                        (kernel.growth_mapping)(index as f32 / (self.growth_resolution - 1) as f32)
+                    })
+                    .collect()
             })
             .collect()
     }
+
+    /// All growth lookup tables concatenated, as laid out in the GPU growth array.
+    pub fn get_growth_vector(&self) -> Vec<f32> {
+        self.get_growth_vectors().concat()
+    }
+}
+
+fn pattern_channel_error() -> LeniaError {
+    LeniaError::InvalidParameter("pattern channel is out of range".to_string())
 }
 
 impl LeniaRule {
+    /// Single-channel rule with one kernel, the classic Lenia setup.
+    pub fn new(kernel_shell: KernelShell, growth_mapping: Mapping) -> Self {
+        Self::multi_channel(1, vec![LeniaKernel::new(kernel_shell, growth_mapping)])
+    }
+
+    /// Single-channel rule summing the growth of several kernels, each scaled by its weight
+    /// ("expanded Lenia"). The growth is not divided by the total weight, weights adding up to 1
+    /// keep it in [-1, 1].
+    pub fn from_kernels(kernels: Vec<LeniaKernel>) -> Self {
+        Self::multi_channel(1, kernels)
+    }
+
+    /// Rule over 1 to `MAX_CHANNELS` channels, the R, G and B components of the world state.
+    pub fn multi_channel(channels: u32, kernels: Vec<LeniaKernel>) -> Self {
+        Self::try_multi_channel(channels, kernels).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_multi_channel(channels: u32, kernels: Vec<LeniaKernel>) -> Result<Self, LeniaError> {
+        let invalid = |message: String| Err(LeniaError::InvalidParameter(message));
+        if channels == 0 || channels > MAX_CHANNELS {
+            return invalid(format!("`channels` must be between 1 and {MAX_CHANNELS}"));
+        }
+        if kernels.is_empty() {
+            return invalid("a rule needs at least one kernel".to_string());
+        }
+        if kernels
+            .iter()
+            .any(|kernel| kernel.source >= channels || kernel.target >= channels)
+        {
+            return invalid("kernel source or target channel is out of range".to_string());
+        }
+        if kernels
+            .iter()
+            .any(|kernel| !(kernel.radius_scale > 0.0 && kernel.radius_scale.is_finite()))
+        {
+            return invalid("kernel radius scale must be positive".to_string());
+        }
+        Ok(Self { channels, kernels })
+    }
+
+    pub fn get_channels(&self) -> u32 {
+        self.channels
+    }
+
+    pub fn get_kernels(&self) -> &[LeniaKernel] {
+        &self.kernels
+    }
+}
+
+impl LeniaKernel {
+    /// Kernel reading from and growing channel 0 with weight 1.
     pub fn new(kernel_shell: KernelShell, growth_mapping: Mapping) -> Self {
         Self {
             kernel_shell,
             growth_mapping,
+            source: 0,
+            target: 0,
+            weight: 1.0,
+            radius_scale: 1.0,
         }
     }
+
+    pub fn with_channels(self, source: u32, target: u32) -> Self {
+        Self {
+            source,
+            target,
+            ..self
+        }
+    }
+
+    pub fn with_weight(self, weight: f32) -> Self {
+        Self { weight, ..self }
+    }
+
+    pub fn with_radius_scale(self, radius_scale: f32) -> Self {
+        Self {
+            radius_scale,
+            ..self
+        }
+    }
+
+    pub fn get_source(&self) -> u32 {
+        self.source
+    }
+
+    pub fn get_target(&self) -> u32 {
+        self.target
+    }
+
+    pub fn get_weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn get_radius_scale(&self) -> f32 {
+        self.radius_scale
+    }
+
+    pub fn get_kernel_shell(&self) -> &KernelShell {
+        &self.kernel_shell
+    }
+
+    pub fn get_growth_mapping(&self) -> &Mapping {
+        &self.growth_mapping
+    }
 }
 
 impl KernelShell {
+    /// Panics on an invalid `beta`, see `try_new`.
     pub fn new(beta: Vec<f32>, kernel_core: Mapping) -> Self {
-        Self { beta, kernel_core }
+        Self::try_new(beta, kernel_core).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(beta: Vec<f32>, kernel_core: Mapping) -> Result<Self, LeniaError> {
+        if beta.is_empty() {
+            return Err(LeniaError::EmptyBeta);
+        }
+        if beta.iter().any(|peak| !(peak.is_finite() && *peak >= 0.0)) {
+            return Err(LeniaError::InvalidParameter(format!(
+                "kernel peaks {beta:?} must be non-negative"
+            )));
+        }
+        Ok(Self { beta, kernel_core })
+    }
+
+    pub fn get_beta(&self) -> &[f32] {
+        &self.beta
+    }
+
+    pub fn get_kernel_core(&self) -> &Mapping {
+        &self.kernel_core
+    }
+
+    /// Kernel value at the normalized distance `r` from its center, zero outside the radius.
+    pub fn profile(&self, r: f32) -> f32 {
+        let kr = r * self.beta.len() as f32;
+        match self.beta.get(kr.floor() as usize) {
+            Some(peak) if r >= 0.0 => peak * (self.kernel_core)(kr.fract()),
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabulates_closures_with_at_least_two_samples() {
+        let mapping = Mapping::new(Arc::new(|x| x * 2.0));
+        for samples in [0, 1, 2] {
+            assert_eq!(
+                mapping.to_spec(samples),
+                MappingSpec::Table {
+                    table: vec![0.0, 2.0]
+                }
+            );
+        }
+        let MappingSpec::Table { table } = mapping.to_spec(5) else {
+            panic!("closures are tabulated");
+        };
+        assert_eq!(table, vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn rules_fit_in_the_rgb_components() {
+        let kernel = |target| {
+            LeniaKernel::new(
+                KernelShell::new(vec![1.0], Mapping::from_type(MappingType::StepCore)),
+                Mapping::from_type(MappingType::StepGrowth {
+                    mu: 0.3,
+                    sigma: 0.1,
+                }),
+            )
+            .with_channels(0, target)
+        };
+        assert!(LeniaRule::try_multi_channel(MAX_CHANNELS, vec![kernel(2)]).is_ok());
+        assert!(LeniaRule::try_multi_channel(4, vec![kernel(3)]).is_err());
+        assert!(LeniaRule::try_multi_channel(2, vec![kernel(2)]).is_err());
     }
 }
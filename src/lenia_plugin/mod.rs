@@ -4,16 +4,21 @@ use bevy::core::cast_slice;
 use bevy::prelude::*;
 use bevy::render::render_resource::*;
 use bevy::render::renderer::RenderDevice;
-use bevy::render::{renderer::RenderQueue, RenderApp, RenderSet};
+use bevy::render::{
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    renderer::RenderQueue,
+    RenderApp, RenderSet,
+};
 
+pub mod diagnostics;
 pub mod lenia_rules;
 pub mod params;
 
 use crate::*;
 
-use self::params::LeniaGPUGrowthArrayBuffer;
+use self::params::{LeniaGPUGrowthArrayBuffer, LeniaGPUKernel, LeniaGPUKernelsBuffer};
 use self::{
-    lenia_rules::LeniaBoard,
+    lenia_rules::{check_world_size, KernelImage, LeniaBoard},
     params::{LeniaGPUParams, LeniaGPUParamsBuffer, LeniaGPUTexture},
 };
 
@@ -29,92 +34,250 @@ impl LeniaRenderPlugin {
 
 impl Plugin for LeniaRenderPlugin {
     fn build(&self, app: &mut App) {
-        let render_app = app.sub_app_mut(RenderApp);
+        // The other Lenia plugins find no world and stay idle, so the app keeps running.
+        if let Err(error) = self.try_build(app) {
+            error!("Lenia is disabled: {error}");
+        }
+    }
+}
+
+impl LeniaRenderPlugin {
+    fn try_build(&self, app: &mut App) -> Result<(), LeniaError> {
+        let render_device = app
+            .get_sub_app(RenderApp)
+            .ok()
+            .and_then(|render_app| render_app.world.get_resource::<RenderDevice>())
+            .ok_or_else(|| LeniaError::Render("no render device".to_string()))?;
+        let limits = render_device.limits();
+        let (width, height) = self.lenia_board.get_space_resolution();
+        if width.max(height) > limits.max_texture_dimension_2d {
+            return Err(LeniaError::InvalidDimensions(format!(
+                "world size {width}x{height} exceeds the texture limit of {}",
+                limits.max_texture_dimension_2d
+            )));
+        }
+        let kernel_layers = self.lenia_board.get_kernel_images().len() as u32;
+        if kernel_layers > limits.max_texture_array_layers {
+            return Err(LeniaError::InvalidParameter(format!(
+                "{kernel_layers} kernels exceed the texture array limit of {}",
+                limits.max_texture_array_layers
+            )));
+        }
+
+        info!("seed: {}", self.lenia_board.get_seed());
+        for diagnostic in self.lenia_board.diagnose() {
+            diagnostic.log();
+        }
+
+        // The world texture created by `LeniaComputePlugin` takes its size from the board.
+        app.insert_resource(LeniaWorldSize(self.lenia_board.get_space_resolution()))
+            .insert_resource(LeniaScenario(Scenario::from_board(&self.lenia_board)))
+            .insert_resource(self.lenia_board.get_state_format());
+        let initial_condition = self.lenia_board.get_initial_condition();
+        if !initial_condition.is_generated_by_shader() {
+            app.insert_resource(LeniaInitialState {
+                condition: initial_condition.clone(),
+                quantization: self.lenia_board.get_quantization(),
+                channels: self.lenia_board.generate_initial_cells(),
+            });
+        }
 
         // Save kernel image for debugging purposes.
-        let kernel_image = self.lenia_board.get_kernel_image();
+        let kernel_images = self.lenia_board.get_kernel_images();
 
-        if let Err(e) = kernel_image.save_image("lenia/assets/kernels/kernel.png") {
-            eprintln!("Image saving error: {}", e);
+        if let Err(e) = kernel_images[0].save_image("lenia/assets/kernels/kernel.png") {
+            warn!("kernel image not saved: {e}");
         };
 
-        // Inserting params
-        let params = self.lenia_board.generate_params();
+        // The kernels, growth array and params are uploaded by `prepare_rule`.
+        app.insert_resource(ActiveLeniaRule::from_board(&self.lenia_board))
+            .add_plugin(ExtractResourcePlugin::<ActiveLeniaRule>::default());
 
-        render_app.insert_resource(params);
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app.insert_resource(self.lenia_board.get_state_format());
 
-        // Initializing params meta, growth array & texture
-        let render_device = render_app.world.get_resource_mut::<RenderDevice>().unwrap();
+        let params_buffer =
+            render_app
+                .world
+                .resource::<RenderDevice>()
+                .create_buffer(&BufferDescriptor {
+                    label: None,
+                    size: std::mem::size_of::<LeniaGPUParams>() as u64,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
 
-        let params_buffer = render_device.create_buffer(&BufferDescriptor {
-            label: None,
-            size: std::mem::size_of::<LeniaGPUParams>() as u64,
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        render_app
+            .insert_resource(LeniaGPUParamsBuffer::new(params_buffer))
+            .insert_resource(UploadedLeniaParams(self.lenia_board.generate_params()))
+            .add_system(prepare_rule.in_set(RenderSet::Prepare))
+            .add_system(
+                prepare_params
+                    .in_set(RenderSet::Prepare)
+                    .after(prepare_rule),
+            );
+        Ok(())
+    }
+}
 
-        let growth_array_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
-            label: None,
-            contents: cast_slice(self.lenia_board.get_growth_vector().as_slice()),
-            usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
-        });
+/// The rule the world runs, as uploaded to the GPU. Replacing it rebuilds the kernel texture,
+/// growth array and params of the render world, so rules can be switched without restarting.
+/// Its board has to fit the running world and hold no more channels than its `StateFormat`.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct ActiveLeniaRule {
+    params: LeniaGPUParams,
+    kernel_images: Vec<KernelImage>, // one texture array layer per kernel
+    growth_vector: Vec<f32>,         // every kernel's lookup table, concatenated
+    kernels: Vec<LeniaGPUKernel>,
+}
 
-        let kernel_image = self.lenia_board.get_kernel_image();
+impl ActiveLeniaRule {
+    pub fn from_board(lenia_board: &LeniaBoard) -> Self {
+        Self {
+            params: lenia_board.generate_params(),
+            kernel_images: lenia_board.get_kernel_images(),
+            growth_vector: lenia_board.get_growth_vector(),
+            kernels: lenia_board.generate_kernel_params(),
+        }
+    }
 
-        let image_size = Extent3d {
-            width: kernel_image.image.width(),
-            height: kernel_image.image.height(),
-            ..default()
-        };
+    pub fn get_params(&self) -> &LeniaGPUParams {
+        &self.params
+    }
 
-        let kernel_texture = render_device.create_texture(&TextureDescriptor {
-            label: None,
-            size: image_size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: TextureDimension::D2,
-            format: TextureFormat::Rgba32Float,
-            usage: TextureUsages::TEXTURE_BINDING
-                | TextureUsages::COPY_DST
-                | TextureUsages::STORAGE_BINDING,
-            view_formats: &[],
-        });
-
-        // Filling texture with data
-        let render_queue = render_app.world.resource::<RenderQueue>();
+    pub fn get_channels(&self) -> u32 {
+        self.params.channels
+    }
+
+    /// Side of the kernel textures, the smallest world the rule runs in.
+    pub fn get_kernel_diameter(&self) -> u32 {
+        self.params.kernel_resolution as u32
+    }
+}
+
+/// Params of the last `ActiveLeniaRule` accepted by `prepare_rule`, matching the kernel texture,
+/// growth array and kernels buffer that are bound. A rejected rule never reaches the shaders.
+#[derive(Resource, Deref)]
+struct UploadedLeniaParams(LeniaGPUParams);
+
+/// Uploads the kernel texture array, growth array and kernels buffer whenever the active rule
+/// changes, the bind groups of the next queue pick them up.
+fn prepare_rule(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    rule: Res<ActiveLeniaRule>,
+    state_format: Res<StateFormat>,
+    world_size: Option<Res<LeniaWorldSize>>,
+    mut uploaded_params: ResMut<UploadedLeniaParams>,
+) {
+    if !rule.is_changed() {
+        return;
+    }
+    // keeps running the previous rule
+    if rule.get_channels() > state_format.channels() {
+        error!(
+            "rule with {} channels does not fit the {:?} state, it is not applied",
+            rule.get_channels(),
+            *state_format
+        );
+        return;
+    }
+    let kernel_layers = rule.kernel_images.len() as u32;
+    if kernel_layers > render_device.limits().max_texture_array_layers {
+        error!(
+            "rule with {kernel_layers} kernels exceeds the texture array limit, it is not applied"
+        );
+        return;
+    }
+    if let Some(world_size) = world_size {
+        if let Err(error) = check_world_size(world_size.0, rule.get_kernel_diameter()) {
+            error!("rule does not fit the running world, it is not applied: {error}");
+            return;
+        }
+    }
+    uploaded_params.0 = rule.params;
+
+    let growth_array_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: None,
+        contents: cast_slice(rule.growth_vector.as_slice()),
+        usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+    });
+
+    let kernels_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: None,
+        contents: cast_slice(rule.kernels.as_slice()),
+        usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+    });
+
+    // All kernels share the board radius, so they stack into layers of one texture array.
+    let kernel_images = &rule.kernel_images;
+    let layer_size = Extent3d {
+        width: kernel_images[0].image.width(),
+        height: kernel_images[0].image.height(),
+        ..default()
+    };
+
+    let kernel_texture = render_device.create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            depth_or_array_layers: kernel_layers,
+            ..layer_size
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba32Float,
+        usage: TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST
+            | TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    });
+
+    // Filling texture layers with data
+    for (layer, kernel_image) in kernel_images.iter().enumerate() {
         render_queue.write_texture(
             ImageCopyTexture {
                 texture: &kernel_texture,
                 mip_level: 0,
-                origin: Origin3d::ZERO,
+                origin: Origin3d {
+                    z: layer as u32,
+                    ..Origin3d::ZERO
+                },
                 aspect: TextureAspect::All,
             },
-            &kernel_image.image.as_bytes(),
+            kernel_image.image.as_bytes(),
             ImageDataLayout {
                 offset: 0,
                 bytes_per_row: NonZeroU32::new(16 * kernel_image.image.width()),
                 rows_per_image: NonZeroU32::new(kernel_image.image.height()),
             },
-            image_size,
+            layer_size,
         );
+    }
 
-        let kernel_texture_view = kernel_texture.create_view(&TextureViewDescriptor::default());
+    let kernel_texture_view = kernel_texture.create_view(&TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        ..default()
+    });
 
-        render_app.insert_resource(LeniaGPUParamsBuffer::new(params_buffer));
-        render_app.insert_resource(LeniaGPUTexture {
-            texture_views: vec![kernel_texture_view],
-        });
-        render_app.insert_resource(LeniaGPUGrowthArrayBuffer::new(growth_array_buffer));
-        render_app.add_system(prepare_params.in_set(RenderSet::Prepare));
-    }
+    commands.insert_resource(LeniaGPUTexture {
+        texture_views: vec![kernel_texture_view],
+    });
+    commands.insert_resource(LeniaGPUGrowthArrayBuffer::new(growth_array_buffer));
+    commands.insert_resource(LeniaGPUKernelsBuffer::new(kernels_buffer));
 }
 
 fn prepare_params(
     render_queue: Res<RenderQueue>,
     params_meta: Res<LeniaGPUParamsBuffer>,
-    params: Res<LeniaGPUParams>,
+    uploaded_params: Res<UploadedLeniaParams>,
+    reseed: Option<Res<LeniaReseed>>,
     time: Res<Time>,
 ) {
-    let params = params.with_delta_time(time.elapsed_seconds());
+    let mut params = uploaded_params.with_delta_time(time.elapsed_seconds());
+    if let Some(reseed) = reseed {
+        params = params.with_noise_offset(reseed.noise_offset);
+    }
     render_queue.write_buffer(&params_meta, 0, cast_slice(&[params]));
 }
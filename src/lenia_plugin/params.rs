@@ -1,4 +1,5 @@
 use bevy::{
+    math::Vec3,
     prelude::{Deref, Resource},
     render::render_resource::{Buffer, TextureView},
 };
@@ -7,23 +8,66 @@ use bytemuck::NoUninit;
 #[repr(C)]
 #[derive(Resource, Clone, Copy, Debug, NoUninit)]
 pub struct LeniaGPUParams {
-    pub random_float: f32,
-    pub kernel_area: f32,
+    pub random_float: f32, // z of the noise offset
     pub kernel_resolution: f32,
     pub delta_time: f32,
     pub dt: f32,
     pub growth_resolution: u32,
+    pub channels: u32,
+    pub kernel_count: u32,
+    pub levels: u32, // discrete states per channel, 0 for continuous
+    pub noise_octaves: u32,
+    pub noise_scale: f32,  // feature size of the first noise octave, in cells
+    pub noise_detail: f32, // feature size of the landscape detail layer, 0 for fBm
+    pub noise_offset_x: f32,
+    pub noise_offset_y: f32,
+    _padding: [u32; 3],
 }
 
 impl LeniaGPUParams {
-    pub fn new(random_float: f32, kernel_area: f32, kernel_resolution: f32, dt: f32, growth_resolution: u32) -> Self {
+    pub fn new(
+        noise_offset: Vec3,
+        kernel_resolution: f32,
+        dt: f32,
+        growth_resolution: u32,
+        channels: u32,
+        kernel_count: u32,
+        levels: u32,
+    ) -> Self {
         Self {
-            random_float,
-            kernel_area,
+            random_float: noise_offset.z,
             kernel_resolution,
             delta_time: 0.0,
             dt,
-            growth_resolution
+            growth_resolution,
+            channels,
+            kernel_count,
+            levels,
+            noise_octaves: 2,
+            noise_scale: 100.0,
+            noise_detail: 40.0,
+            noise_offset_x: noise_offset.x,
+            noise_offset_y: noise_offset.y,
+            _padding: [0; 3],
+        }
+    }
+
+    /// Perlin fBm generated by the `init` shader.
+    pub fn with_noise(&self, noise_octaves: u32, noise_scale: f32) -> Self {
+        Self {
+            noise_octaves,
+            noise_scale,
+            noise_detail: 0.0,
+            ..*self
+        }
+    }
+
+    /// Product of two noise layers generated by the `init` shader.
+    pub fn with_landscape(&self, noise_scale: f32, noise_detail: f32) -> Self {
+        Self {
+            noise_scale,
+            noise_detail,
+            ..*self
         }
     }
 
@@ -33,6 +77,37 @@ impl LeniaGPUParams {
             ..*self
         }
     }
+
+    /// Region of the noise volume the `init` shader samples, see `noise_offset`.
+    pub fn with_noise_offset(&self, noise_offset: Vec3) -> Self {
+        Self {
+            random_float: noise_offset.z,
+            noise_offset_x: noise_offset.x,
+            noise_offset_y: noise_offset.y,
+            ..*self
+        }
+    }
+}
+
+/// Per-kernel entry of the kernels storage buffer, in the same order as the kernel texture layers.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, NoUninit)]
+pub struct LeniaGPUKernel {
+    pub source_channel: u32,
+    pub target_channel: u32,
+    pub weight: f32,
+    pub area: f32,
+}
+
+impl LeniaGPUKernel {
+    pub fn new(source_channel: u32, target_channel: u32, weight: f32, area: f32) -> Self {
+        Self {
+            source_channel,
+            target_channel,
+            weight,
+            area,
+        }
+    }
 }
 
 #[derive(Resource, Deref)]
@@ -56,4 +131,13 @@ impl LeniaGPUGrowthArrayBuffer {
     pub fn new(buffer: Buffer) -> Self {
         Self(buffer)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Resource, Deref)]
+pub struct LeniaGPUKernelsBuffer(Buffer);
+
+impl LeniaGPUKernelsBuffer {
+    pub fn new(buffer: Buffer) -> Self {
+        Self(buffer)
+    }
+}
@@ -1,8 +1,46 @@
 #![allow(unused_imports)]
+pub mod colormap;
 pub mod compute_plugin;
+pub mod controls_plugin;
+pub mod cpu_backend;
+#[cfg(feature = "editor")]
+pub mod editor_plugin;
+pub mod error;
+pub mod initial_condition;
 pub mod lenia_plugin;
+pub mod pattern;
+pub mod recorder;
+pub mod reload_plugin;
+pub mod scenario;
+pub mod snapshot;
+pub mod statistics;
+pub use colormap::{Colormap, ColormapError, LUT_SIZE};
 pub use compute_plugin::*;
-pub use lenia_plugin::{lenia_rules::*, LeniaRenderPlugin};
+pub use controls_plugin::{LeniaControls, LeniaControlsPlugin, LeniaRules, SelectRule};
+pub use cpu_backend::{ConvolutionMode, CpuKernel, CpuLenia};
+#[cfg(feature = "editor")]
+pub use editor_plugin::{LeniaEditor, LeniaEditorPlugin};
+pub use error::LeniaError;
+pub use initial_condition::{InitialCondition, InitialImage};
+pub use lenia_plugin::{
+    diagnostics::{Diagnostic, Severity},
+    lenia_rules::*,
+    ActiveLeniaRule, LeniaRenderPlugin,
+};
+pub use pattern::{
+    library::{Creature, CreatureLibrary, LibraryError},
+    rle::{LeniaParams, LeniaPatternFile, RleError},
+    Pattern, PatternStamp,
+};
+pub use recorder::{CropRegion, RecordFormat, Recorder, RecorderError, RecorderSettings};
+pub use reload_plugin::{ScenarioReloadPlugin, WatchedScenario};
+pub use scenario::{
+    asset::{ScenarioAsset, ScenarioLoader},
+    expression::{Expression, ExpressionError},
+    InitialConditionSpec, KernelSpec, PatternSpec, Scenario, ScenarioError,
+};
+pub use snapshot::{Snapshot, SnapshotError};
+pub use statistics::{ChannelStatistics, OCCUPIED_THRESHOLD};
 pub use std::sync::Arc;
 
 pub use bevy::{
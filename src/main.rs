@@ -2,7 +2,7 @@ use lenia::*;
 
 const SIZE: (u32, u32) = (1280, 720);
 
-fn main() {
+fn main() -> Result<(), LeniaError> {
     let gol_kernel_core = Mapping::new(Arc::new(|x| {
         if x < 0.25 {
             0.5
@@ -13,14 +13,14 @@ fn main() {
         }
     }));
 
-    let kernel_shell = KernelShell::new(vec![1.0], gol_kernel_core);
+    let kernel_shell = KernelShell::try_new(vec![1.0], gol_kernel_core)?;
     let kernel_image = KernelImage::new(&kernel_shell, 20, 1.0);
 
-    kernel_image
-        .save_image("lenia/assets/kernels/gol_kernel_x10.png")
-        .unwrap();
+    if let Err(error) = kernel_image.save_image("lenia/assets/kernels/gol_kernel_x10.png") {
+        eprintln!("kernel image not saved: {error}");
+    }
 
-    let lenia_board = LeniaBoard::new(
+    let lenia_board = LeniaBoard::try_new(
         LeniaRule::new(
             kernel_shell,
             Mapping::from_type(MappingType::StepGrowth {
@@ -32,7 +32,7 @@ fn main() {
         2,
         1.0,
         100,
-    );
+    )?;
 
     lenia_board
         .get_growth_vector()
@@ -53,5 +53,8 @@ fn main() {
         }))
         .add_plugin(LeniaRenderPlugin::new(lenia_board))
         .add_plugin(LeniaComputePlugin)
+        .add_plugin(LeniaControlsPlugin)
         .run();
+
+    Ok(())
 }
@@ -0,0 +1,244 @@
+use std::{fmt, fs, path::Path};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{
+    rle::{parse_fraction, whole_number, LeniaParams, RleError},
+    Pattern, PatternStamp,
+};
+use crate::lenia_plugin::lenia_rules::LeniaBoard;
+
+/// A species from the library, with the parameters it was found with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Creature {
+    pub code: String, // short identifier, e.g. `O2u`
+    pub name: String,
+    pub cname: Option<String>, // name in Chinese, as published
+    pub params: LeniaParams,
+    pub pattern: Pattern,
+}
+
+/// Creatures loaded from the `animals.json` format published with Lenia.
+#[derive(Clone, Debug, Default)]
+pub struct CreatureLibrary {
+    creatures: Vec<Creature>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LibraryError {
+    Io(String),
+    Json(String),
+    Creature { code: String, error: RleError },
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibraryError::Io(error) => write!(f, "{error}"),
+            LibraryError::Json(error) => write!(f, "invalid creature library: {error}"),
+            LibraryError::Creature { code, error } => write!(f, "creature `{code}`: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+// Entries without params and cells are section headings, e.g. a family name.
+#[derive(Deserialize)]
+struct AnimalEntry {
+    code: String,
+    name: String,
+    cname: Option<String>,
+    params: Option<AnimalParams>,
+    cells: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnimalParams {
+    #[serde(rename = "R")]
+    r: f32,
+    #[serde(rename = "T")]
+    t: f32,
+    b: Value, // "1,1/3" or a plain number
+    m: f32,
+    s: f32,
+    kn: u32,
+    gn: u32,
+}
+
+impl CreatureLibrary {
+    pub fn from_json(json: &str) -> Result<Self, LibraryError> {
+        let entries: Vec<AnimalEntry> =
+            serde_json::from_str(json).map_err(|e| LibraryError::Json(e.to_string()))?;
+
+        let creatures = entries
+            .into_iter()
+            .filter_map(AnimalEntry::into_creature)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { creatures })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LibraryError> {
+        Self::from_json(&fs::read_to_string(path).map_err(|e| LibraryError::Io(e.to_string()))?)
+    }
+
+    pub fn creatures(&self) -> &[Creature] {
+        &self.creatures
+    }
+
+    pub fn get_by_code(&self, code: &str) -> Option<&Creature> {
+        self.creatures.iter().find(|creature| creature.code == code)
+    }
+
+    /// Case-insensitive exact match on the species name.
+    pub fn get_by_name(&self, name: &str) -> Option<&Creature> {
+        self.creatures
+            .iter()
+            .find(|creature| creature.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Creatures whose code or name contains `query`, ignoring case.
+    pub fn search<'a>(&'a self, query: &'a str) -> impl Iterator<Item = &'a Creature> + 'a {
+        let query = query.to_lowercase();
+        self.creatures.iter().filter(move |creature| {
+            creature.code.to_lowercase().contains(&query)
+                || creature.name.to_lowercase().contains(&query)
+        })
+    }
+}
+
+impl Creature {
+    /// A board running the creature's rule, with the creature in the middle of an empty world.
+    pub fn to_board(
+        &self,
+        space_resolution: (u32, u32),
+        growth_resolution: u32,
+    ) -> Result<LeniaBoard, RleError> {
+        let position = self.pattern.centered_in(space_resolution);
+        Ok(self
+            .params
+            .to_board(space_resolution, growth_resolution)?
+            .try_with_pattern(PatternStamp::new(self.pattern.clone(), position))?)
+    }
+}
+
+impl AnimalEntry {
+    fn into_creature(self) -> Option<Result<Creature, LibraryError>> {
+        let (params, cells) = (self.params?, self.cells?);
+        let creature = params.to_lenia_params().and_then(|params| {
+            Ok(Creature {
+                code: self.code.clone(),
+                name: self.name,
+                cname: self.cname,
+                params,
+                pattern: Pattern::from_rle(&cells)?,
+            })
+        });
+        Some(creature.map_err(|error| LibraryError::Creature {
+            code: self.code,
+            error,
+        }))
+    }
+}
+
+impl AnimalParams {
+    fn to_lenia_params(&self) -> Result<LeniaParams, RleError> {
+        let b = match &self.b {
+            Value::String(b) => b
+                .split(',')
+                .map(|peak| {
+                    parse_fraction(peak).ok_or_else(|| RleError::InvalidParameter(format!("b={b}")))
+                })
+                .collect::<Result<_, _>>()?,
+            Value::Number(b) => vec![b.as_f64().unwrap_or_default() as f32],
+            b => return Err(RleError::InvalidParameter(format!("b={b}"))),
+        };
+
+        Ok(LeniaParams {
+            r: whole_number("R", self.r)?,
+            t: self.t,
+            b,
+            m: self.m,
+            s: self.s,
+            kn: self.kn,
+            gn: self.gn,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu_backend::CpuLenia;
+
+    const SIZE: (u32, u32) = (64, 64);
+
+    fn library() -> CreatureLibrary {
+        CreatureLibrary::load("assets/creatures/animals.json").unwrap()
+    }
+
+    // Live cells relative to the top-left one, and where that one is.
+    fn shape(cpu_lenia: &CpuLenia) -> (Vec<(u32, u32)>, (u32, u32)) {
+        let live: Vec<(u32, u32)> = (0..SIZE.0 * SIZE.1)
+            .filter(|index| cpu_lenia.cells(0)[*index as usize] > 0.5)
+            .map(|index| (index % SIZE.0, index / SIZE.0))
+            .collect();
+        let origin = live.iter().fold((u32::MAX, u32::MAX), |(x0, y0), (x, y)| {
+            (x0.min(*x), y0.min(*y))
+        });
+        let shape = live
+            .iter()
+            .map(|(x, y)| (x - origin.0, y - origin.1))
+            .collect();
+        (shape, origin)
+    }
+
+    #[test]
+    fn every_bundled_creature_converts_to_a_board() {
+        let library = library();
+        for creature in library.creatures() {
+            creature
+                .to_board(SIZE, 100)
+                .unwrap_or_else(|error| panic!("{}: {error}", creature.code));
+        }
+        assert!(library
+            .creatures()
+            .iter()
+            .any(|creature| creature.params.b.len() > 1));
+        assert!(library
+            .creatures()
+            .iter()
+            .any(|creature| creature.params.kn == 4));
+    }
+
+    #[test]
+    fn rejects_fractional_radii() {
+        let json = r#"[{ "code": "X", "name": "X", "cells": "o!",
+            "params": { "R": 12.7, "T": 10, "b": "1", "m": 0.15, "s": 0.015, "kn": 1, "gn": 1 } }]"#;
+        assert!(matches!(
+            CreatureLibrary::from_json(json),
+            Err(LibraryError::Creature {
+                error: RleError::InvalidParameter(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn game_of_life_spaceships_move() {
+        let library = library();
+        for code in ["GL", "LWSS", "MWSS", "HWSS"] {
+            let creature = library.get_by_code(code).unwrap();
+            let mut cpu_lenia = CpuLenia::new(&creature.to_board(SIZE, 100).unwrap());
+            let (initial_shape, initial_origin) = shape(&cpu_lenia);
+            for _ in 0..4 {
+                cpu_lenia.step();
+            }
+            let (moved_shape, moved_origin) = shape(&cpu_lenia);
+            assert_eq!(moved_shape, initial_shape, "{code} changed shape");
+            assert_ne!(moved_origin, initial_origin, "{code} did not move");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+pub mod library;
+pub mod rle;
+
+use crate::error::LeniaError;
+
+/// A rectangular, single-channel block of cells, e.g. a creature to place in the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    size: (u32, u32), // (width, height)
+    cells: Vec<f32>,  // row-major, values in [0, 1]
+}
+
+/// A pattern placed in the world with its top-left corner at `position`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternStamp {
+    pub pattern: Pattern,
+    pub position: (i32, i32),
+    pub channel: u32,
+}
+
+impl Pattern {
+    pub fn new(size: (u32, u32), cells: Vec<f32>) -> Self {
+        Self::try_new(size, cells).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_new(size: (u32, u32), cells: Vec<f32>) -> Result<Self, LeniaError> {
+        if (size.0 as usize).checked_mul(size.1 as usize) != Some(cells.len()) {
+            return Err(LeniaError::InvalidDimensions(format!(
+                "{} cells do not fill a {}x{} pattern",
+                cells.len(),
+                size.0,
+                size.1
+            )));
+        }
+        Ok(Self { size, cells })
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn cells(&self) -> &[f32] {
+        &self.cells
+    }
+
+    pub fn get_cell(&self, x: u32, y: u32) -> f32 {
+        self.cells[(y * self.size.0 + x) as usize]
+    }
+
+    /// Overwrites a row-major world grid with the pattern, wrapping around the edges.
+    pub fn stamp(&self, cells: &mut [f32], space_resolution: (u32, u32), position: (i32, i32)) {
+        let (width, height) = (space_resolution.0 as i32, space_resolution.1 as i32);
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                let world_x = (position.0 + x as i32).rem_euclid(width);
+                let world_y = (position.1 + y as i32).rem_euclid(height);
+                cells[(world_y * width + world_x) as usize] = self.get_cell(x, y);
+            }
+        }
+    }
+
+    /// Position placing the pattern in the middle of a world.
+    pub fn centered_in(&self, space_resolution: (u32, u32)) -> (i32, i32) {
+        (
+            (space_resolution.0 as i32 - self.size.0 as i32) / 2,
+            (space_resolution.1 as i32 - self.size.1 as i32) / 2,
+        )
+    }
+}
+
+impl PatternStamp {
+    pub fn new(pattern: Pattern, position: (i32, i32)) -> Self {
+        Self {
+            pattern,
+            position,
+            channel: 0,
+        }
+    }
+
+    pub fn with_channel(self, channel: u32) -> Self {
+        Self { channel, ..self }
+    }
+}
@@ -0,0 +1,526 @@
+//! The Lenia dialect of run-length encoded patterns.
+//!
+//! Cell values are quantized to 0..=255: `.` or `b` is 0, `o` is 255, `A`..`X` are 1 to 24 and
+//! two-letter codes `pA`..`yO` cover 25 to 255. A row ends with `$` and the pattern with `!`,
+//! and any code may be preceded by a repeat count.
+
+use std::{fmt, fs, path::Path};
+
+use super::Pattern;
+use crate::error::LeniaError;
+use crate::lenia_plugin::lenia_rules::{
+    KernelShell, LeniaBoard, LeniaKernel, LeniaRule, Mapping, MappingType,
+};
+
+/// Largest pattern `Pattern::from_rle` builds, far beyond any creature, so a corrupt repeat
+/// count fails instead of allocating gigabytes.
+pub const MAX_PATTERN_CELLS: usize = 1 << 22;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RleError {
+    InvalidCell(String),
+    InvalidHeader(String),
+    InvalidParameter(String),
+    MissingParameter(&'static str),
+    Io(String),
+    Board(LeniaError), // parameters the board constructors reject
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RleError::InvalidCell(code) => write!(f, "invalid RLE cell code `{code}`"),
+            RleError::InvalidHeader(line) => write!(f, "invalid RLE header `{line}`"),
+            RleError::InvalidParameter(param) => write!(f, "invalid Lenia parameter `{param}`"),
+            RleError::MissingParameter(name) => write!(f, "missing Lenia parameter `{name}`"),
+            RleError::Io(error) => write!(f, "{error}"),
+            RleError::Board(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+impl From<LeniaError> for RleError {
+    fn from(error: LeniaError) -> Self {
+        RleError::Board(error)
+    }
+}
+
+/// The parameters attached to published Lenia creatures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeniaParams {
+    pub r: u32,      // kernel radius in cells
+    pub t: f32,      // time resolution, dt = 1 / T
+    pub b: Vec<f32>, // kernel peaks
+    pub m: f32,      // growth center
+    pub s: f32,      // growth width
+    pub kn: u32,     // kernel core: 1 polynomial, 2 exponential, 3 step, 4 staircase
+    pub gn: u32,     // growth function: 1 polynomial, 2 gaussian, 3 step
+}
+
+/// A pattern file: optional name, parameters and cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeniaPatternFile {
+    pub name: Option<String>,
+    pub params: LeniaParams,
+    pub pattern: Pattern,
+}
+
+impl Pattern {
+    pub fn from_rle(rle: &str) -> Result<Self, RleError> {
+        let mut rows: Vec<Vec<f32>> = vec![vec![]];
+        let mut width = 0;
+        let mut count: Option<usize> = None;
+        let mut chars = rle.chars().filter(|c| !c.is_whitespace());
+
+        while let Some(c) = chars.next() {
+            let value = match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap() as usize;
+                    count = count
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(digit))
+                        .filter(|count| *count <= MAX_PATTERN_CELLS)
+                        .map(Some)
+                        .ok_or_else(|| {
+                            RleError::InvalidCell(format!("repeat count above {MAX_PATTERN_CELLS}"))
+                        })?;
+                    continue;
+                }
+                '!' => break,
+                '$' => {
+                    let new_rows = count.take().unwrap_or(1);
+                    check_pattern_size(width, rows.len() + new_rows)?;
+                    rows.extend(std::iter::repeat_n(vec![], new_rows));
+                    continue;
+                }
+                '.' | 'b' => 0,
+                'o' => 255,
+                'A'..='X' => c as u32 - 'A' as u32 + 1,
+                'p'..='y' => match chars.next() {
+                    Some(low @ 'A'..='X') => {
+                        (c as u32 - 'p' as u32) * 24 + (low as u32 - 'A' as u32 + 25)
+                    }
+                    other => {
+                        return Err(RleError::InvalidCell(
+                            other.map_or(c.to_string(), |low| format!("{c}{low}")),
+                        ))
+                    }
+                },
+                _ => return Err(RleError::InvalidCell(c.to_string())),
+            };
+            if value > 255 {
+                return Err(RleError::InvalidCell(c.to_string()));
+            }
+
+            let run = count.take().unwrap_or(1);
+            let row_length = rows.last().map_or(0, Vec::len) + run;
+            check_pattern_size(width.max(row_length), rows.len())?;
+            width = width.max(row_length);
+            let row = rows.last_mut().unwrap();
+            row.extend(std::iter::repeat_n(value as f32 / 255.0, run));
+        }
+
+        // Trailing empty cells are omitted from rows, pad every row to the widest one.
+        let height = rows.len();
+        let cells = rows
+            .into_iter()
+            .flat_map(|mut row| {
+                row.resize(width, 0.0);
+                row
+            })
+            .collect();
+
+        Ok(Self::try_new((width as u32, height as u32), cells)?)
+    }
+
+    pub fn to_rle(&self) -> String {
+        let rows: Vec<String> = self
+            .cells
+            .chunks(self.size.0.max(1) as usize)
+            .map(|row| {
+                let mut runs: Vec<(usize, u32)> = vec![];
+                for cell in row {
+                    let value = (cell.clamp(0.0, 1.0) * 255.0).round() as u32;
+                    match runs.last_mut() {
+                        Some((count, last)) if *last == value => *count += 1,
+                        _ => runs.push((1, value)),
+                    }
+                }
+                if matches!(runs.last(), Some((_, 0))) {
+                    runs.pop();
+                }
+
+                runs.into_iter()
+                    .map(|(count, value)| match count {
+                        1 => encode_value(value),
+                        _ => format!("{count}{}", encode_value(value)),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        rows.join("$") + "!"
+    }
+}
+
+// Empty rows still take memory, so they count as one cell wide.
+fn check_pattern_size(width: usize, height: usize) -> Result<(), RleError> {
+    if width.max(1).saturating_mul(height) > MAX_PATTERN_CELLS {
+        return Err(RleError::InvalidCell(format!(
+            "pattern of {width}x{height} cells is larger than {MAX_PATTERN_CELLS} cells"
+        )));
+    }
+    Ok(())
+}
+
+fn encode_value(value: u32) -> String {
+    match value {
+        0 => ".".to_string(),
+        1..=24 => char::from_u32('A' as u32 + value - 1).unwrap().to_string(),
+        _ => {
+            let high = char::from_u32('p' as u32 + (value - 25) / 24).unwrap();
+            let low = char::from_u32('A' as u32 + (value - 25) % 24).unwrap();
+            format!("{high}{low}")
+        }
+    }
+}
+
+impl LeniaParams {
+    /// Parses the inside of `Lenia(...)`, e.g. `R=13,T=10,b=[1/2,1],m=0.15,s=0.015,kn=1,gn=1`.
+    pub fn parse(params: &str) -> Result<Self, RleError> {
+        let mut fields = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        for (index, c) in params.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    fields.push(&params[start..index]);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        fields.push(&params[start..]);
+
+        let field = |name: &'static str| {
+            fields
+                .iter()
+                .filter_map(|field| field.split_once('='))
+                .find(|(key, _)| key.trim() == name)
+                .map(|(_, value)| value.trim())
+                .ok_or(RleError::MissingParameter(name))
+        };
+        let number = |name: &'static str| {
+            let value = field(name)?;
+            parse_fraction(value)
+                .ok_or_else(|| RleError::InvalidParameter(format!("{name}={value}")))
+        };
+
+        let b = field("b")?;
+        let b = b
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|peak| {
+                parse_fraction(peak).ok_or_else(|| RleError::InvalidParameter(format!("b={b}")))
+            })
+            .collect::<Result<Vec<f32>, RleError>>()?;
+
+        Ok(Self {
+            r: whole_number("R", number("R")?)?,
+            t: number("T")?,
+            b,
+            m: number("m")?,
+            s: number("s")?,
+            kn: whole_number("kn", number("kn")?)?,
+            gn: whole_number("gn", number("gn")?)?,
+        })
+    }
+
+    /// The `MappingType` selected by the kernel core index `kn`.
+    pub fn kernel_core_type(&self) -> Result<MappingType, RleError> {
+        Ok(match self.kn {
+            1 => MappingType::PolynomialCore { alpha: 4.0 },
+            2 => MappingType::GaussianCore { alpha: 4.0 },
+            3 => MappingType::StepCore,
+            4 => MappingType::StaircaseCore,
+            kn => return Err(RleError::InvalidParameter(format!("kn={kn}"))),
+        })
+    }
+
+    /// The `MappingType` selected by the growth function index `gn`, centered on `m` with width `s`.
+    pub fn growth_type(&self) -> Result<MappingType, RleError> {
+        let (mu, sigma) = (self.m, self.s);
+        Ok(match self.gn {
+            1 => MappingType::PolynomialGrowth {
+                mu,
+                sigma,
+                alpha: 4.0,
+            },
+            2 => MappingType::GaussianGrowth { mu, sigma },
+            3 => MappingType::StepGrowth { mu, sigma },
+            gn => return Err(RleError::InvalidParameter(format!("gn={gn}"))),
+        })
+    }
+
+    pub fn kernel_core(&self) -> Result<Mapping, RleError> {
+        Ok(Mapping::from_type(self.kernel_core_type()?))
+    }
+
+    pub fn growth_mapping(&self) -> Result<Mapping, RleError> {
+        Ok(Mapping::from_type(self.growth_type()?))
+    }
+
+    pub fn to_rule(&self) -> Result<LeniaRule, RleError> {
+        Ok(LeniaRule::try_multi_channel(
+            1,
+            vec![LeniaKernel::new(
+                KernelShell::try_new(self.b.clone(), self.kernel_core()?)?,
+                self.growth_mapping()?,
+            )],
+        )?)
+    }
+
+    pub fn to_board(
+        &self,
+        space_resolution: (u32, u32),
+        growth_resolution: u32,
+    ) -> Result<LeniaBoard, RleError> {
+        Ok(LeniaBoard::try_new(
+            self.to_rule()?,
+            space_resolution,
+            self.r,
+            1.0 / self.t,
+            growth_resolution,
+        )?)
+    }
+}
+
+impl fmt::Display for LeniaParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b: Vec<String> = self.b.iter().map(|peak| format_fraction(*peak)).collect();
+        let b = match b.len() {
+            1 => b[0].clone(),
+            _ => format!("[{}]", b.join(",")),
+        };
+        write!(
+            f,
+            "R={},T={},b={},m={},s={},kn={},gn={}",
+            self.r, self.t, b, self.m, self.s, self.kn, self.gn
+        )
+    }
+}
+
+impl LeniaPatternFile {
+    /// Parses a Golly-style file: `#N name` comment, `x = .., y = .., rule = Lenia(..)` header,
+    /// then the cells.
+    pub fn parse(content: &str) -> Result<Self, RleError> {
+        let mut name = None;
+        let mut params = None;
+        let mut rle = String::new();
+
+        for line in content.lines().map(str::trim) {
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(pattern_name) = comment.strip_prefix('N') {
+                    name = Some(pattern_name.trim().to_string());
+                }
+            } else if line.starts_with('x') && params.is_none() {
+                let rule = line
+                    .split_once("rule")
+                    .and_then(|(_, rule)| rule.trim_start().strip_prefix('='))
+                    .map(str::trim)
+                    .and_then(|rule| rule.strip_prefix("Lenia("))
+                    .and_then(|rule| rule.strip_suffix(')'))
+                    .ok_or_else(|| RleError::InvalidHeader(line.to_string()))?;
+                params = Some(LeniaParams::parse(rule)?);
+            } else {
+                rle.push_str(line);
+            }
+        }
+
+        Ok(Self {
+            name,
+            params: params.ok_or_else(|| RleError::InvalidHeader("missing header".to_string()))?,
+            pattern: Pattern::from_rle(&rle)?,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RleError> {
+        Self::parse(&fs::read_to_string(path).map_err(|e| RleError::Io(e.to_string()))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RleError> {
+        fs::write(path, self.to_string()).map_err(|e| RleError::Io(e.to_string()))
+    }
+}
+
+impl fmt::Display for LeniaPatternFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "#N {name}")?;
+        }
+        let (width, height) = self.pattern.get_size();
+        writeln!(
+            f,
+            "x = {width}, y = {height}, rule = Lenia({})",
+            self.params
+        )?;
+
+        // Keep lines short, like Golly does, without splitting two-letter codes.
+        let mut line_length = 0;
+        for c in self.pattern.to_rle().chars() {
+            if line_length >= 70 && !c.is_ascii_uppercase() {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            write!(f, "{c}")?;
+            line_length += 1;
+        }
+        writeln!(f)
+    }
+}
+
+/// Parses `0.25`, `1` or `1/4`.
+pub fn parse_fraction(value: &str) -> Option<f32> {
+    match value.trim().split_once('/') {
+        Some((numerator, denominator)) => {
+            Some(numerator.trim().parse::<f32>().ok()? / denominator.trim().parse::<f32>().ok()?)
+        }
+        None => value.trim().parse().ok(),
+    }
+}
+
+/// Integer parameters such as `R`, rejected when negative or fractional rather than rounded into
+/// a different rule.
+pub fn whole_number(name: &str, value: f32) -> Result<u32, RleError> {
+    if value.fract() != 0.0 || !(0.0..=u32::MAX as f32).contains(&value) {
+        return Err(RleError::InvalidParameter(format!("{name}={value}")));
+    }
+    Ok(value as u32)
+}
+
+/// Writes values that are small fractions, such as the usual kernel peaks, as `k/n`.
+pub fn format_fraction(value: f32) -> String {
+    for denominator in 1..=12 {
+        let numerator = (value * denominator as f32).round();
+        if (numerator / denominator as f32 - value).abs() < 1e-6 {
+            return match denominator {
+                1 => format!("{numerator}"),
+                _ => format!("{numerator}/{denominator}"),
+            };
+        }
+    }
+    format!("{value}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_cell_codes_and_counts() {
+        let pattern = Pattern::from_rle("2.A$o pA yO!").unwrap();
+        assert_eq!(pattern.get_size(), (3, 2));
+        let values: Vec<u32> = pattern
+            .cells()
+            .iter()
+            .map(|cell| (cell * 255.0).round() as u32)
+            .collect();
+        // trailing empty cells of the shorter row are padded
+        assert_eq!(values, vec![0, 0, 1, 255, 25, 255]);
+        // blank rows
+        assert_eq!(Pattern::from_rle("o2$o!").unwrap().get_size(), (1, 3));
+    }
+
+    #[test]
+    fn rejects_invalid_codes() {
+        assert_eq!(
+            Pattern::from_rle("oZ!"),
+            Err(RleError::InvalidCell("Z".to_string()))
+        );
+        assert_eq!(
+            Pattern::from_rle("pz!"),
+            Err(RleError::InvalidCell("pz".to_string()))
+        );
+        // yP would be 256
+        assert!(Pattern::from_rle("yP!").is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_patterns() {
+        for rle in [
+            "99999999999999999999999o!",
+            "4000000000o!",
+            "5000000$o!",
+            "3000o$3000$o!",
+        ] {
+            assert!(
+                matches!(Pattern::from_rle(rle), Err(RleError::InvalidCell(_))),
+                "{rle}"
+            );
+        }
+        assert_eq!(
+            Pattern::from_rle("2048o$2046$o!").unwrap().get_size(),
+            (2048, 2048)
+        );
+        assert!(Pattern::try_new((2, 2), vec![0.0; 3]).is_err());
+        assert!(Pattern::try_new((u32::MAX, u32::MAX), vec![]).is_err());
+    }
+
+    #[test]
+    fn round_trips_every_value() {
+        let cells: Vec<f32> = (0..=255).map(|value| value as f32 / 255.0).collect();
+        let pattern = Pattern::new((16, 16), cells);
+        assert_eq!(Pattern::from_rle(&pattern.to_rle()).unwrap(), pattern);
+    }
+
+    #[test]
+    fn rejects_fractional_and_negative_integer_params() {
+        for params in [
+            "R=-5,T=10,b=1,m=0.15,s=0.015,kn=1,gn=1",
+            "R=12.7,T=10,b=1,m=0.15,s=0.015,kn=1,gn=1",
+            "R=13,T=10,b=1,m=0.15,s=0.015,kn=1.5,gn=1",
+            "R=13,T=10,b=1,m=0.15,s=0.015,kn=1,gn=-1",
+        ] {
+            assert!(
+                matches!(
+                    LeniaParams::parse(params),
+                    Err(RleError::InvalidParameter(_))
+                ),
+                "{params}"
+            );
+        }
+        assert_eq!(whole_number("R", 13.0), Ok(13));
+    }
+
+    #[test]
+    fn parses_and_prints_params() {
+        let params = LeniaParams::parse("R=13,T=10,b=[1/2,1],m=0.15,s=0.015,kn=4,gn=2").unwrap();
+        assert_eq!(params.r, 13);
+        assert_eq!(params.b, vec![0.5, 1.0]);
+        assert_eq!(params.kernel_core_type(), Ok(MappingType::StaircaseCore));
+        assert_eq!(LeniaParams::parse(&params.to_string()), Ok(params));
+
+        assert_eq!(
+            LeniaParams::parse("R=13,T=10,b=1,m=0.15,s=0.015,kn=1"),
+            Err(RleError::MissingParameter("gn"))
+        );
+        assert!(LeniaParams::parse("R=13,T=10,b=1,m=0.15,s=0.015,kn=9,gn=1")
+            .unwrap()
+            .to_rule()
+            .is_err());
+    }
+
+    #[test]
+    fn round_trips_pattern_files() {
+        let file = LeniaPatternFile::load("assets/patterns/orbium.rle").unwrap();
+        assert_eq!(file.params.r, 13);
+        assert_eq!(LeniaPatternFile::parse(&file.to_string()), Ok(file));
+        assert!(LeniaPatternFile::parse("o!").is_err());
+    }
+}
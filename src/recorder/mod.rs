@@ -0,0 +1,248 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops, Delay, Frame, RgbImage,
+};
+
+/// Quantization speed of the GIF encoder, from 1 (best colors) to 30 (fastest).
+const GIF_SPEED: i32 = 10;
+
+/// Output of a `Recorder`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecordFormat {
+    PngSequence, // `frame_00000.png`, `frame_00001.png`, ... in a directory
+    #[default]
+    Gif,
+}
+
+/// Part of the world to record, in cells from the top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CropRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecorderSettings {
+    pub every: u64, // steps between frames
+    pub format: RecordFormat,
+    pub frame_delay: u32, // milliseconds each GIF frame is shown
+    pub crop: Option<CropRegion>,
+    pub downscale: u32, // frames are shrunk by this factor, averaging the cells
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            every: 10,
+            format: RecordFormat::Gif,
+            frame_delay: 50,
+            crop: None,
+            downscale: 1,
+        }
+    }
+}
+
+impl RecorderSettings {
+    pub fn with_every(self, every: u64) -> Self {
+        Self { every, ..self }
+    }
+
+    pub fn with_format(self, format: RecordFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_frame_delay(self, frame_delay: u32) -> Self {
+        Self {
+            frame_delay,
+            ..self
+        }
+    }
+
+    pub fn with_crop(self, crop: CropRegion) -> Self {
+        Self {
+            crop: Some(crop),
+            ..self
+        }
+    }
+
+    pub fn with_downscale(self, downscale: u32) -> Self {
+        Self { downscale, ..self }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecorderError {
+    Io(String),
+    Encode(String),
+    Invalid(String),
+}
+
+impl fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecorderError::Io(error) => write!(f, "{error}"),
+            RecorderError::Encode(error) => write!(f, "failed to encode frame: {error}"),
+            RecorderError::Invalid(error) => write!(f, "invalid recording: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RecorderError {}
+
+/// Writes colored frames of the world to a PNG sequence or an animated GIF.
+///
+/// The caller colors the world, e.g. with `LeniaColors::to_image`, for every step `is_due`
+/// accepts. A GIF is only complete once the recorder is finished or dropped.
+pub struct Recorder {
+    settings: RecorderSettings,
+    path: PathBuf, // directory of a PNG sequence, file of a GIF
+    frames: u64,
+    gif: Option<GifEncoder<BufWriter<File>>>,
+}
+
+impl Recorder {
+    /// Starts a recording at `path`, the directory of a PNG sequence or the GIF file.
+    pub fn create(
+        path: impl AsRef<Path>,
+        settings: RecorderSettings,
+    ) -> Result<Self, RecorderError> {
+        if settings.every == 0 || settings.downscale == 0 {
+            return Err(RecorderError::Invalid(
+                "`every` and `downscale` must be at least 1".to_string(),
+            ));
+        }
+        let path = path.as_ref().to_path_buf();
+        let io_error = |e: std::io::Error| RecorderError::Io(format!("{}: {e}", path.display()));
+
+        let gif = match settings.format {
+            RecordFormat::PngSequence => {
+                fs::create_dir_all(&path).map_err(io_error)?;
+                None
+            }
+            RecordFormat::Gif => {
+                if let Some(directory) = path.parent().filter(|parent| parent != &Path::new("")) {
+                    fs::create_dir_all(directory).map_err(io_error)?;
+                }
+                let file = BufWriter::new(File::create(&path).map_err(io_error)?);
+                let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|e| RecorderError::Encode(e.to_string()))?;
+                Some(encoder)
+            }
+        };
+
+        Ok(Self {
+            settings,
+            path,
+            frames: 0,
+            gif,
+        })
+    }
+
+    pub fn get_settings(&self) -> &RecorderSettings {
+        &self.settings
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Whether a frame should be taken at `step`.
+    pub fn is_due(&self, step: u64) -> bool {
+        step.is_multiple_of(self.settings.every)
+    }
+
+    /// Crops, downscales and appends a frame of the whole world.
+    pub fn record(&mut self, world: &RgbImage) -> Result<(), RecorderError> {
+        let frame = prepare_frame(world, self.settings.crop, self.settings.downscale)?;
+        match &mut self.gif {
+            Some(encoder) => {
+                let delay = Delay::from_numer_denom_ms(self.settings.frame_delay, 1);
+                let rgba = image::DynamicImage::ImageRgb8(frame).into_rgba8();
+                encoder
+                    .encode_frame(Frame::from_parts(rgba, 0, 0, delay))
+                    .map_err(|e| RecorderError::Encode(e.to_string()))?;
+            }
+            None => {
+                let path = self.path.join(format!("frame_{:05}.png", self.frames));
+                frame
+                    .save(&path)
+                    .map_err(|e| RecorderError::Encode(format!("{}: {e}", path.display())))?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Completes the recording, returning the number of frames written.
+    pub fn finish(self) -> u64 {
+        // the GIF trailer is written when the encoder is dropped
+        self.frames
+    }
+}
+
+/// Applies the crop region, then averages `downscale`² blocks of pixels into one.
+pub fn prepare_frame(
+    world: &RgbImage,
+    crop: Option<CropRegion>,
+    downscale: u32,
+) -> Result<RgbImage, RecorderError> {
+    let frame = match crop {
+        Some(CropRegion {
+            x,
+            y,
+            width,
+            height,
+        }) => {
+            if width == 0 || height == 0 || x + width > world.width() || y + height > world.height()
+            {
+                return Err(RecorderError::Invalid(format!(
+                    "crop region {width}x{height} at ({x}, {y}) does not fit a {}x{} world",
+                    world.width(),
+                    world.height()
+                )));
+            }
+            imageops::crop_imm(world, x, y, width, height).to_image()
+        }
+        None => world.clone(),
+    };
+    if downscale <= 1 {
+        return Ok(frame);
+    }
+
+    let (width, height) = (
+        (frame.width() / downscale).max(1),
+        (frame.height() / downscale).max(1),
+    );
+    Ok(RgbImage::from_fn(width, height, |x, y| {
+        let mut sum = [0u32; 3];
+        let mut count = 0;
+        for dy in 0..downscale {
+            for dx in 0..downscale {
+                let Some(pixel) = frame.get_pixel_checked(x * downscale + dx, y * downscale + dy)
+                else {
+                    continue;
+                };
+                for (sum, component) in sum.iter_mut().zip(pixel.0) {
+                    *sum += component as u32;
+                }
+                count += 1;
+            }
+        }
+        image::Rgb(sum.map(|sum| (sum / count.max(1)) as u8))
+    }))
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+use crate::compute_plugin::{ApplyScenario, SimulationClock};
+use crate::scenario::asset::{ScenarioAsset, ScenarioLoader, SCENARIO_EXTENSIONS};
+
+/// Applies a scenario file to the running world every time it is saved, to tune a rule without
+/// restarting the app.
+///
+/// The `AssetPlugin` has to watch for changes, e.g.
+/// `DefaultPlugins.set(AssetPlugin { watch_for_changes: true, ..default() })`, and the path is
+/// relative to its asset folder. The file has to end in `.lenia.ron` or `.lenia.toml`, the
+/// extensions `ScenarioLoader` claims. Only edits made after startup are applied, the world is
+/// expected to have started from the same file.
+pub struct ScenarioReloadPlugin {
+    path: String,
+    reinitialize: bool, // restart from the initial condition of the file on every change
+}
+
+impl ScenarioReloadPlugin {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            reinitialize: false,
+        }
+    }
+
+    pub fn with_reinitialize(self, reinitialize: bool) -> Self {
+        Self {
+            reinitialize,
+            ..self
+        }
+    }
+}
+
+impl Plugin for ScenarioReloadPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<SimulationClock>() {
+            error!("ScenarioReloadPlugin needs LeniaComputePlugin, added first");
+            return;
+        }
+        let Some(asset_server) = app.world.get_resource::<AssetServer>().cloned() else {
+            error!("ScenarioReloadPlugin needs the AssetPlugin");
+            return;
+        };
+        if !SCENARIO_EXTENSIONS
+            .iter()
+            .any(|extension| self.path.ends_with(&format!(".{extension}")))
+        {
+            error!(
+                "{}: scenario files followed by ScenarioReloadPlugin have to end in `.lenia.ron` \
+                 or `.lenia.toml`",
+                self.path
+            );
+            return;
+        }
+        // the asset type is registered before anything of it is loaded
+        app.add_asset::<ScenarioAsset>();
+        asset_server.add_loader(ScenarioLoader);
+        // kept for the whole run, so the asset is never unloaded
+        let handle = asset_server.load(self.path.as_str());
+
+        app.insert_resource(WatchedScenario {
+            path: self.path.clone(),
+            handle,
+            reinitialize: self.reinitialize,
+        })
+        .add_system(reload_scenario);
+    }
+}
+
+/// The scenario file followed by `ScenarioReloadPlugin`.
+#[derive(Resource)]
+pub struct WatchedScenario {
+    pub path: String,
+    pub handle: Handle<ScenarioAsset>,
+    pub reinitialize: bool,
+}
+
+fn reload_scenario(
+    mut asset_events: EventReader<AssetEvent<ScenarioAsset>>,
+    watched: Res<WatchedScenario>,
+    scenarios: Res<Assets<ScenarioAsset>>,
+    mut apply_events: EventWriter<ApplyScenario>,
+) {
+    for event in asset_events.iter() {
+        // files that fail to parse are reported by the asset server and keep the last rule
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        let Some(ScenarioAsset(scenario)) = (*handle == watched.handle)
+            .then(|| scenarios.get(handle))
+            .flatten()
+        else {
+            continue;
+        };
+        info!("reloading {}", watched.path);
+        apply_events.send(ApplyScenario {
+            scenario: scenario.clone(),
+            reinitialize: watched.reinitialize,
+        });
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    prelude::Deref,
+    reflect::TypeUuid,
+};
+
+use super::{extension, Scenario, ScenarioError};
+
+/// A scenario loaded through the `AssetServer`, reloaded when its file changes if the
+/// `AssetPlugin` watches for changes.
+#[derive(Clone, Debug, Deref, TypeUuid)]
+#[uuid = "5b0f0f4e-8d3a-4c55-9b2e-7f1a6c2d9e41"]
+pub struct ScenarioAsset(pub Scenario);
+
+/// Extensions `ScenarioLoader` claims, so other `.ron` and `.toml` assets keep their loaders.
+pub const SCENARIO_EXTENSIONS: [&str; 2] = ["lenia.ron", "lenia.toml"];
+
+/// Loads `.lenia.ron` and `.lenia.toml` files as `ScenarioAsset`s.
+#[derive(Default)]
+pub struct ScenarioLoader;
+
+impl AssetLoader for ScenarioLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let content = std::str::from_utf8(bytes)?;
+            let scenario = match extension(load_context.path()).as_str() {
+                "ron" => Scenario::from_ron(content)?,
+                "toml" => Scenario::from_toml(content)?,
+                extension => return Err(ScenarioError::UnknownFormat(extension.to_string()).into()),
+            };
+            load_context.set_default_asset(LoadedAsset::new(ScenarioAsset(scenario)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &SCENARIO_EXTENSIONS
+    }
+}
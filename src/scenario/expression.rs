@@ -0,0 +1,431 @@
+//! Arithmetic expressions of `x`, used to describe mappings that have no `MappingType`.
+//!
+//! Supports numbers, `x`, `pi`, `e`, `+ - * / ^`, comparisons (`< <= > >=`, giving 1 or 0),
+//! parentheses and the functions `exp ln sqrt abs sin cos tan floor ceil min max pow clamp`.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpressionError(pub String);
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// A parsed expression, evaluated with `Expression::eval`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Number(f32),
+    X,
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function {
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Floor,
+    Ceil,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    Number(f32),
+    Identifier(&'a str),
+    Symbol(&'a str),
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.comparison()?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(ExpressionError(format!("unexpected {token:?}"))),
+        }
+    }
+
+    pub fn eval(&self, x: f32) -> f32 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::X => x,
+            Expression::Negate(operand) => -operand.eval(x),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.eval(x), right.eval(x));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                    Operator::Less => (left < right) as u8 as f32,
+                    Operator::LessEqual => (left <= right) as u8 as f32,
+                    Operator::Greater => (left > right) as u8 as f32,
+                    Operator::GreaterEqual => (left >= right) as u8 as f32,
+                }
+            }
+            Expression::Call(function, arguments) => {
+                let arguments: Vec<f32> = arguments.iter().map(|a| a.eval(x)).collect();
+                match function {
+                    Function::Exp => arguments[0].exp(),
+                    Function::Ln => arguments[0].ln(),
+                    Function::Sqrt => arguments[0].sqrt(),
+                    Function::Abs => arguments[0].abs(),
+                    Function::Sin => arguments[0].sin(),
+                    Function::Cos => arguments[0].cos(),
+                    Function::Tan => arguments[0].tan(),
+                    Function::Floor => arguments[0].floor(),
+                    Function::Ceil => arguments[0].ceil(),
+                    Function::Min => arguments[0].min(arguments[1]),
+                    Function::Max => arguments[0].max(arguments[1]),
+                    Function::Pow => arguments[0].powf(arguments[1]),
+                    // `f32::clamp` panics on reversed or NaN bounds
+                    Function::Clamp => arguments[0].max(arguments[1]).min(arguments[2]),
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_digit() || c == '.' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            // scientific notation, e.g. 1.5e-3
+            let length = match rest[length..].strip_prefix(['e', 'E']) {
+                Some(exponent) => {
+                    let sign = exponent.starts_with(['+', '-']) as usize;
+                    let digits = exponent[sign..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(exponent.len() - sign);
+                    if digits > 0 {
+                        length + 1 + sign + digits
+                    } else {
+                        length
+                    }
+                }
+                None => length,
+            };
+            let number = rest[..length]
+                .parse()
+                .map_err(|_| ExpressionError(format!("invalid number `{}`", &rest[..length])))?;
+            tokens.push(Token::Number(number));
+            length
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Identifier(&rest[..length]));
+            length
+        } else if rest.starts_with("<=") || rest.starts_with(">=") {
+            tokens.push(Token::Symbol(&rest[..2]));
+            2
+        } else if "+-*/^<>(),".contains(c) {
+            tokens.push(Token::Symbol(&rest[..1]));
+            1
+        } else {
+            return Err(ExpressionError(format!("unexpected character `{c}`")));
+        };
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Deepest nesting of parentheses, operators and calls the parser accepts, so neither parsing
+/// nor evaluating a hostile expression can overflow the stack.
+const MAX_NESTING: usize = 256;
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    depth: usize, // nesting of the expression being parsed
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ExpressionError> {
+        match self.next() {
+            Some(Token::Symbol(s)) if s == symbol => Ok(()),
+            token => Err(ExpressionError(format!(
+                "expected `{symbol}`, found {token:?}"
+            ))),
+        }
+    }
+
+    fn descend(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(ExpressionError(format!(
+                "nested deeper than {MAX_NESTING} levels"
+            )));
+        }
+        Ok(())
+    }
+
+    // Each operator of a chain adds a level: `a + b + c` is `(a + b) + c`.
+    fn binary(
+        &mut self,
+        operators: &[(&str, Operator)],
+        operand: fn(&mut Self) -> Result<Expression, ExpressionError>,
+    ) -> Result<Expression, ExpressionError> {
+        let depth = self.depth;
+        let mut left = operand(self)?;
+        while let Some(Token::Symbol(symbol)) = self.peek() {
+            let Some((_, operator)) = operators.iter().find(|(s, _)| *s == symbol) else {
+                break;
+            };
+            self.position += 1;
+            self.descend()?;
+            left = Expression::Binary(*operator, Box::new(left), Box::new(operand(self)?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary(
+            &[
+                ("<", Operator::Less),
+                ("<=", Operator::LessEqual),
+                (">", Operator::Greater),
+                (">=", Operator::GreaterEqual),
+            ],
+            Self::additive,
+        )
+    }
+
+    fn additive(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary(
+            &[("+", Operator::Add), ("-", Operator::Subtract)],
+            Self::term,
+        )
+    }
+
+    fn term(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary(
+            &[("*", Operator::Multiply), ("/", Operator::Divide)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        if self.peek() == Some(Token::Symbol("-")) {
+            self.position += 1;
+            self.descend()?;
+            let operand = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Negate(Box::new(operand)));
+        }
+        self.power()
+    }
+
+    // `^` is right associative and binds tighter than unary minus: -x^2 is -(x^2).
+    fn power(&mut self) -> Result<Expression, ExpressionError> {
+        let base = self.primary()?;
+        if self.peek() == Some(Token::Symbol("^")) {
+            self.position += 1;
+            self.descend()?;
+            let exponent = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Symbol("(")) => {
+                self.descend()?;
+                let expression = self.comparison()?;
+                self.depth -= 1;
+                self.expect(")")?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(name)) => match name {
+                "x" => Ok(Expression::X),
+                "pi" => Ok(Expression::Number(std::f32::consts::PI)),
+                "e" => Ok(Expression::Number(std::f32::consts::E)),
+                _ => self.call(name),
+            },
+            token => Err(ExpressionError(format!("unexpected {token:?}"))),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expression, ExpressionError> {
+        let (function, arity) = match name {
+            "exp" => (Function::Exp, 1),
+            "ln" => (Function::Ln, 1),
+            "sqrt" => (Function::Sqrt, 1),
+            "abs" => (Function::Abs, 1),
+            "sin" => (Function::Sin, 1),
+            "cos" => (Function::Cos, 1),
+            "tan" => (Function::Tan, 1),
+            "floor" => (Function::Floor, 1),
+            "ceil" => (Function::Ceil, 1),
+            "min" => (Function::Min, 2),
+            "max" => (Function::Max, 2),
+            "pow" => (Function::Pow, 2),
+            "clamp" => (Function::Clamp, 3),
+            _ => return Err(ExpressionError(format!("unknown name `{name}`"))),
+        };
+
+        self.expect("(")?;
+        self.descend()?;
+        let mut arguments = vec![self.comparison()?];
+        while self.peek() == Some(Token::Symbol(",")) {
+            self.position += 1;
+            arguments.push(self.comparison()?);
+        }
+        self.depth -= 1;
+        self.expect(")")?;
+
+        if arguments.len() != arity {
+            return Err(ExpressionError(format!(
+                "`{name}` takes {arity} argument(s), found {}",
+                arguments.len()
+            )));
+        }
+        Ok(Expression::Call(function, arguments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, x: f32) -> f32 {
+        Expression::parse(source).unwrap().eval(x)
+    }
+
+    #[test]
+    fn tokenizes_numbers_names_and_symbols() {
+        assert_eq!(
+            tokenize("1.5e-3*x <= max(2,.5)").unwrap(),
+            vec![
+                Token::Number(1.5e-3),
+                Token::Symbol("*"),
+                Token::Identifier("x"),
+                Token::Symbol("<="),
+                Token::Identifier("max"),
+                Token::Symbol("("),
+                Token::Number(2.0),
+                Token::Symbol(","),
+                Token::Number(0.5),
+                Token::Symbol(")"),
+            ]
+        );
+        // `e` without exponent digits is the constant
+        assert_eq!(
+            tokenize("2e").unwrap(),
+            vec![Token::Number(2.0), Token::Identifier("e")]
+        );
+        assert!(tokenize("x % 2").is_err());
+        assert!(tokenize("1.2.3").is_err());
+    }
+
+    #[test]
+    fn respects_precedence_and_associativity() {
+        assert_eq!(eval("1 + 2 * 3", 0.0), 7.0);
+        assert_eq!(eval("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(eval("8 - 2 - 1", 0.0), 5.0);
+        assert_eq!(eval("-x^2", 3.0), -9.0);
+        assert_eq!(eval("2^3^2", 0.0), 512.0);
+        assert_eq!(eval("2^-1", 0.0), 0.5);
+        assert_eq!(eval("x + 1 > 2", 1.5), 1.0);
+        assert_eq!(eval("x + 1 > 2", 0.5), 0.0);
+    }
+
+    #[test]
+    fn rejects_wrong_arity_and_unknown_names() {
+        assert!(Expression::parse("min(x)").is_err());
+        assert!(Expression::parse("exp(x, 1)").is_err());
+        assert!(Expression::parse("clamp(x, 0)").is_err());
+        assert!(Expression::parse("foo(x)").is_err());
+        assert!(Expression::parse("(x + 1").is_err());
+        assert!(Expression::parse("x x").is_err());
+    }
+
+    #[test]
+    fn clamps_without_panicking() {
+        assert_eq!(eval("clamp(x, 0, 1)", 1.5), 1.0);
+        assert_eq!(eval("clamp(x, 0, 1)", -0.5), 0.0);
+        assert_eq!(eval("clamp(x, 0, 1)", 0.25), 0.25);
+        // reversed bounds give the upper bound instead of panicking
+        assert_eq!(eval("clamp(x, 1, 0)", 0.5), 0.0);
+        assert!(!eval("clamp(x, 0 / 0, 1)", 0.5).is_nan());
+    }
+
+    #[test]
+    fn rejects_deeply_nested_expressions() {
+        let parens = format!("{}x{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(Expression::parse(&parens).is_err());
+        assert!(Expression::parse(&format!("{}x", "-".repeat(100_000))).is_err());
+        assert!(Expression::parse(&format!("x{}", "^x".repeat(100_000))).is_err());
+        assert!(Expression::parse(&format!("x{}", "+1".repeat(100_000))).is_err());
+        assert!(Expression::parse(&format!(
+            "{}x{}",
+            "abs(".repeat(100_000),
+            ")".repeat(100_000)
+        ))
+        .is_err());
+
+        assert_eq!(
+            eval(&format!("{}x{}", "(".repeat(100), ")".repeat(100)), 2.0),
+            2.0
+        );
+        assert_eq!(eval(&format!("x{}", "+1".repeat(100)), 0.0), 100.0);
+    }
+}
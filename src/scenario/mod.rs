@@ -0,0 +1,477 @@
+pub mod asset;
+pub mod expression;
+
+use std::{fmt, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use self::expression::ExpressionError;
+use crate::compute_plugin::StateFormat;
+use crate::error::LeniaError;
+use crate::initial_condition::{InitialCondition, InitialImage};
+use crate::lenia_plugin::lenia_rules::{
+    KernelShell, LeniaBoard, LeniaKernel, LeniaRule, Mapping, MappingSpec, MAX_CHANNELS,
+};
+use crate::pattern::{
+    rle::{LeniaPatternFile, RleError},
+    Pattern, PatternStamp,
+};
+
+/// Declarative description of a board, loaded from RON or TOML instead of built in code.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub world_size: (u32, u32), // (width, height)
+    pub radius: u32,            // cells per kernel radius
+    pub dt: f32,
+    pub growth_resolution: u32,
+    #[serde(default = "default_channels")]
+    pub channels: u32, // 1 to `MAX_CHANNELS` (3), one per RGB component of the state
+    pub kernels: Vec<KernelSpec>,
+    #[serde(default)]
+    pub initial_condition: InitialConditionSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>, // random if `None`
+    #[serde(default)]
+    pub state_format: StateFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<u32>, // discrete states per channel (P)
+}
+
+/// One `LeniaKernel` of the scenario rule.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KernelSpec {
+    pub beta: Vec<f32>, // kernel peaks
+    pub core: MappingSpec,
+    pub growth: MappingSpec,
+    #[serde(default)]
+    pub source: u32,
+    #[serde(default)]
+    pub target: u32,
+    #[serde(default = "default_one")]
+    pub weight: f32,
+    #[serde(default = "default_one")]
+    pub radius_scale: f32,
+}
+
+/// Serializable form of an `InitialCondition`, images are referenced by file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InitialConditionSpec {
+    Landscape {
+        #[serde(default = "default_noise_scale")]
+        scale: f32,
+        #[serde(default = "default_noise_detail")]
+        detail: f32,
+    },
+    #[serde(alias = "Noise")]
+    Perlin {
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default = "default_noise_scale")]
+        scale: f32,
+    },
+    Uniform {
+        #[serde(default)]
+        low: f32,
+        #[serde(default = "default_one")]
+        high: f32,
+    },
+    Blobs {
+        count: u32,
+        radius: f32,
+        #[serde(default = "default_region")]
+        region: f32,
+    },
+    Empty,
+    Image {
+        file: String,
+    },
+    Patterns {
+        patterns: Vec<PatternSpec>,
+    },
+}
+
+impl Default for InitialConditionSpec {
+    fn default() -> Self {
+        InitialConditionSpec::Landscape {
+            scale: default_noise_scale(),
+            detail: default_noise_detail(),
+        }
+    }
+}
+
+/// A pattern stamped into the initial world, given inline as RLE cells or as a `.rle` file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PatternSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<(i32, i32)>, // top-left corner, centered in the world if `None`
+    #[serde(default)]
+    pub channel: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioError {
+    Io(String),
+    Parse(String),
+    UnknownFormat(String), // file extension other than `ron` or `toml`
+    Mapping(ExpressionError),
+    Pattern(RleError),
+    Image(String),
+    Invalid(String),
+    Board(LeniaError), // rejected by the board constructors
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(error) => write!(f, "{error}"),
+            ScenarioError::Parse(error) => write!(f, "invalid scenario: {error}"),
+            ScenarioError::UnknownFormat(extension) => {
+                write!(
+                    f,
+                    "unknown scenario format `{extension}`, expected `ron` or `toml`"
+                )
+            }
+            ScenarioError::Mapping(error) => write!(f, "{error}"),
+            ScenarioError::Pattern(error) => write!(f, "{error}"),
+            ScenarioError::Image(error) => write!(f, "invalid initial image: {error}"),
+            ScenarioError::Invalid(error) => write!(f, "invalid scenario: {error}"),
+            ScenarioError::Board(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<ExpressionError> for ScenarioError {
+    fn from(error: ExpressionError) -> Self {
+        ScenarioError::Mapping(error)
+    }
+}
+
+impl From<RleError> for ScenarioError {
+    fn from(error: RleError) -> Self {
+        ScenarioError::Pattern(error)
+    }
+}
+
+impl From<LeniaError> for ScenarioError {
+    fn from(error: LeniaError) -> Self {
+        ScenarioError::Board(error)
+    }
+}
+
+fn default_channels() -> u32 {
+    1
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_octaves() -> u32 {
+    2
+}
+
+fn default_noise_scale() -> f32 {
+    100.0
+}
+
+fn default_noise_detail() -> f32 {
+    40.0
+}
+
+fn default_region() -> f32 {
+    0.5
+}
+
+impl KernelSpec {
+    pub fn to_kernel(&self) -> Result<LeniaKernel, ScenarioError> {
+        Ok(LeniaKernel::new(
+            KernelShell::try_new(self.beta.clone(), Mapping::from_spec(self.core.clone())?)?,
+            Mapping::from_spec(self.growth.clone())?,
+        )
+        .with_channels(self.source, self.target)
+        .with_weight(self.weight)
+        .with_radius_scale(self.radius_scale))
+    }
+}
+
+impl Scenario {
+    pub fn from_ron(ron: &str) -> Result<Self, ScenarioError> {
+        ron::from_str(ron).map_err(|e| ScenarioError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ScenarioError> {
+        toml::from_str(toml).map_err(|e| ScenarioError::Parse(e.to_string()))
+    }
+
+    pub fn to_ron(&self) -> Result<String, ScenarioError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| ScenarioError::Parse(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, ScenarioError> {
+        toml::to_string_pretty(self).map_err(|e| ScenarioError::Parse(e.to_string()))
+    }
+
+    /// Loads a `.ron` or `.toml` scenario, picking the format from the extension. Files meant
+    /// for the `AssetServer` end in `.lenia.ron` or `.lenia.toml`, see `ScenarioLoader`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| ScenarioError::Io(e.to_string()))?;
+        match extension(path).as_str() {
+            "ron" => Self::from_ron(&content),
+            "toml" => Self::from_toml(&content),
+            extension => Err(ScenarioError::UnknownFormat(extension.to_string())),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        let path = path.as_ref();
+        let content = match extension(path).as_str() {
+            "ron" => self.to_ron()?,
+            "toml" => self.to_toml()?,
+            extension => return Err(ScenarioError::UnknownFormat(extension.to_string())),
+        };
+        fs::write(path, content).map_err(|e| ScenarioError::Io(e.to_string()))
+    }
+
+    pub fn to_board(&self) -> Result<LeniaBoard, ScenarioError> {
+        self.validate()?;
+
+        let kernels = self
+            .kernels
+            .iter()
+            .map(KernelSpec::to_kernel)
+            .collect::<Result<_, _>>()?;
+
+        let mut board = LeniaBoard::try_new(
+            LeniaRule::try_multi_channel(self.channels, kernels)?,
+            self.world_size,
+            self.radius,
+            self.dt,
+            self.growth_resolution,
+        )?;
+        board = board
+            .try_with_initial_condition(self.initial_condition.to_condition(self.world_size)?)?;
+        if let Some(seed) = self.seed {
+            board = board.with_seed(seed);
+        }
+        if let Some(levels) = self.quantization {
+            board = board.try_with_quantization(levels)?;
+        }
+        Ok(board.try_with_state_format(self.state_format)?)
+    }
+
+    /// Describes an existing board. Mappings built from closures are tabulated with the board's
+    /// growth resolution, patterns are stored inline.
+    pub fn from_board(board: &LeniaBoard) -> Self {
+        let rule = board.get_rule();
+        let samples = board.get_growth_resolution();
+        let kernels = rule
+            .get_kernels()
+            .iter()
+            .map(|kernel| KernelSpec {
+                beta: kernel.get_kernel_shell().get_beta().to_vec(),
+                core: kernel.get_kernel_shell().get_kernel_core().to_spec(samples),
+                growth: kernel.get_growth_mapping().to_spec(samples),
+                source: kernel.get_source(),
+                target: kernel.get_target(),
+                weight: kernel.get_weight(),
+                radius_scale: kernel.get_radius_scale(),
+            })
+            .collect();
+
+        let initial_condition = InitialConditionSpec::from_condition(board.get_initial_condition());
+
+        Self {
+            world_size: board.get_space_resolution(),
+            radius: board.get_dx(),
+            dt: board.get_dt(),
+            growth_resolution: board.get_growth_resolution(),
+            channels: rule.get_channels(),
+            kernels,
+            initial_condition,
+            seed: Some(board.get_seed()),
+            state_format: board.get_state_format(),
+            quantization: board.get_quantization(),
+        }
+    }
+
+    /// Whether both scenarios run the same rule, regardless of world size, initial condition
+    /// and seed.
+    pub fn same_rule(&self, other: &Scenario) -> bool {
+        self.radius == other.radius
+            && self.dt == other.dt
+            && self.growth_resolution == other.growth_resolution
+            && self.channels == other.channels
+            && self.kernels == other.kernels
+            && self.quantization == other.quantization
+    }
+
+    // Names the offending scenario field for what the board constructors would reject.
+    fn validate(&self) -> Result<(), ScenarioError> {
+        let invalid = |message: String| Err(ScenarioError::Invalid(message));
+
+        if self.channels == 0 || self.channels > MAX_CHANNELS {
+            return invalid(format!("`channels` must be between 1 and {MAX_CHANNELS}"));
+        }
+        if self.kernels.is_empty() {
+            return invalid("a scenario needs at least one kernel".to_string());
+        }
+        if self.growth_resolution < 2 {
+            return invalid("`growth_resolution` must be at least 2".to_string());
+        }
+        for (index, kernel) in self.kernels.iter().enumerate() {
+            if kernel.source >= self.channels || kernel.target >= self.channels {
+                return invalid(format!("kernel {index}: channel is out of range"));
+            }
+            if kernel.radius_scale <= 0.0 {
+                return invalid(format!("kernel {index}: `radius_scale` must be positive"));
+            }
+        }
+
+        if self.channels > self.state_format.channels() {
+            return invalid(format!(
+                "state format {:?} cannot hold {} channels",
+                self.state_format, self.channels
+            ));
+        }
+        if self.quantization == Some(0) {
+            return invalid("`quantization` needs at least one level".to_string());
+        }
+
+        let max_radius_scale = self
+            .kernels
+            .iter()
+            .map(|kernel| kernel.radius_scale)
+            .fold(0.0, f32::max);
+        let diameter = (self.radius as f32 * max_radius_scale).ceil() as u32 * 2 + 1;
+        if diameter > self.world_size.0 || diameter > self.world_size.1 {
+            return invalid("kernel diameter is larger than `world_size`".to_string());
+        }
+
+        // the other parameters are checked by `InitialCondition::validate`
+        match &self.initial_condition {
+            InitialConditionSpec::Patterns { patterns }
+                if patterns
+                    .iter()
+                    .any(|pattern| pattern.channel >= self.channels) =>
+            {
+                invalid("pattern channel is out of range".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl InitialConditionSpec {
+    pub fn to_condition(&self, world_size: (u32, u32)) -> Result<InitialCondition, ScenarioError> {
+        Ok(match self {
+            InitialConditionSpec::Landscape { scale, detail } => InitialCondition::Landscape {
+                scale: *scale,
+                detail: *detail,
+            },
+            InitialConditionSpec::Perlin { octaves, scale } => InitialCondition::Perlin {
+                octaves: *octaves,
+                scale: *scale,
+            },
+            InitialConditionSpec::Uniform { low, high } => InitialCondition::Uniform {
+                low: *low,
+                high: *high,
+            },
+            InitialConditionSpec::Blobs {
+                count,
+                radius,
+                region,
+            } => InitialCondition::Blobs {
+                count: *count,
+                radius: *radius,
+                region: *region,
+            },
+            InitialConditionSpec::Empty => InitialCondition::Empty,
+            InitialConditionSpec::Image { file } => InitialCondition::Image(
+                InitialImage::load(file)
+                    .map_err(|e| ScenarioError::Image(format!("{file}: {e}")))?,
+            ),
+            InitialConditionSpec::Patterns { patterns } => InitialCondition::Patterns(
+                patterns
+                    .iter()
+                    .map(|pattern| pattern.to_stamp(world_size))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+
+    /// Describes an existing condition, patterns are stored inline.
+    pub fn from_condition(condition: &InitialCondition) -> Self {
+        match condition {
+            InitialCondition::Landscape { scale, detail } => InitialConditionSpec::Landscape {
+                scale: *scale,
+                detail: *detail,
+            },
+            InitialCondition::Perlin { octaves, scale } => InitialConditionSpec::Perlin {
+                octaves: *octaves,
+                scale: *scale,
+            },
+            InitialCondition::Uniform { low, high } => InitialConditionSpec::Uniform {
+                low: *low,
+                high: *high,
+            },
+            InitialCondition::Blobs {
+                count,
+                radius,
+                region,
+            } => InitialConditionSpec::Blobs {
+                count: *count,
+                radius: *radius,
+                region: *region,
+            },
+            InitialCondition::Empty => InitialConditionSpec::Empty,
+            InitialCondition::Image(image) => InitialConditionSpec::Image {
+                file: image.get_path().to_string(),
+            },
+            InitialCondition::Patterns(stamps) => InitialConditionSpec::Patterns {
+                patterns: stamps
+                    .iter()
+                    .map(|stamp| PatternSpec {
+                        rle: Some(stamp.pattern.to_rle()),
+                        file: None,
+                        position: Some(stamp.position),
+                        channel: stamp.channel,
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl PatternSpec {
+    pub fn to_stamp(&self, world_size: (u32, u32)) -> Result<PatternStamp, ScenarioError> {
+        let pattern = match (&self.rle, &self.file) {
+            (Some(rle), None) => Pattern::from_rle(rle)?,
+            (None, Some(file)) => LeniaPatternFile::load(file)?.pattern,
+            _ => {
+                return Err(ScenarioError::Invalid(
+                    "a pattern needs exactly one of `rle` and `file`".to_string(),
+                ))
+            }
+        };
+        let position = self
+            .position
+            .unwrap_or_else(|| pattern.centered_in(world_size));
+        Ok(PatternStamp::new(pattern, position).with_channel(self.channel))
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
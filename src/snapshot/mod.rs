@@ -0,0 +1,295 @@
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use crate::cpu_backend::CpuLenia;
+use crate::scenario::{Scenario, ScenarioError};
+
+const MAGIC: &[u8; 8] = b"LENIASNP";
+const VERSION: u32 = 1;
+
+/// The full state of a running world: its rule, the step it was taken at and every channel at
+/// f32 precision.
+///
+/// On disk a snapshot is little-endian binary: the magic bytes, the format version, the
+/// scenario as length-prefixed RON, the step, width, height and channel count, then the cells
+/// of each channel, row-major.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub scenario: Scenario, // rule and seed, `world_size` is the size of `channels`
+    pub step: u64,
+    pub channels: Vec<Vec<f32>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    Io(String),
+    Format(String),
+    Scenario(ScenarioError),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "{error}"),
+            SnapshotError::Format(error) => write!(f, "invalid snapshot: {error}"),
+            SnapshotError::Scenario(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error.to_string())
+    }
+}
+
+impl From<ScenarioError> for SnapshotError {
+    fn from(error: ScenarioError) -> Self {
+        SnapshotError::Scenario(error)
+    }
+}
+
+impl Snapshot {
+    /// Fails if `channels` do not hold `scenario.channels` grids of `scenario.world_size`.
+    pub fn new(
+        scenario: Scenario,
+        step: u64,
+        channels: Vec<Vec<f32>>,
+    ) -> Result<Self, SnapshotError> {
+        let (width, height) = scenario.world_size;
+        let cells = width as usize * height as usize;
+        if channels.len() != scenario.channels as usize
+            || channels.iter().any(|channel| channel.len() != cells)
+        {
+            return Err(SnapshotError::Format(format!(
+                "expected {} channels of {width}x{height} cells",
+                scenario.channels
+            )));
+        }
+        Ok(Self {
+            scenario,
+            step,
+            channels,
+        })
+    }
+
+    /// Captures a CPU simulation, whose rule is described by `scenario`.
+    pub fn from_cpu(
+        mut scenario: Scenario,
+        step: u64,
+        cpu_lenia: &CpuLenia,
+    ) -> Result<Self, SnapshotError> {
+        scenario.world_size = cpu_lenia.get_space_resolution();
+        let channels = (0..cpu_lenia.get_channels())
+            .map(|channel| cpu_lenia.cells(channel).to_vec())
+            .collect();
+        Self::new(scenario, step, channels)
+    }
+
+    /// A CPU simulation continuing from the snapshot.
+    pub fn to_cpu(&self) -> Result<CpuLenia, SnapshotError> {
+        let mut cpu_lenia = CpuLenia::new(&self.scenario.to_board()?);
+        for (channel, cells) in self.channels.iter().enumerate() {
+            cpu_lenia.set_cells(channel as u32, cells.clone());
+        }
+        Ok(cpu_lenia)
+    }
+
+    /// Runs the scenario on the CPU from its seeded initial condition up to the snapshot's step.
+    /// Matches the snapshot as long as the world was neither painted nor resized.
+    pub fn replay(&self) -> Result<CpuLenia, SnapshotError> {
+        let mut cpu_lenia = CpuLenia::new(&self.scenario.to_board()?);
+        for _ in 0..self.step {
+            cpu_lenia.step();
+        }
+        Ok(cpu_lenia)
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), SnapshotError> {
+        let scenario = self.scenario.to_ron()?;
+        let (width, height) = self.scenario.world_size;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(scenario.len() as u32).to_le_bytes())?;
+        writer.write_all(scenario.as_bytes())?;
+        writer.write_all(&self.step.to_le_bytes())?;
+        for value in [width, height, self.channels.len() as u32] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for cells in &self.channels {
+            let bytes: Vec<u8> = cells.iter().flat_map(|cell| cell.to_le_bytes()).collect();
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a whole snapshot, lengths stored in it are checked against the bytes that follow
+    /// before anything is allocated.
+    pub fn read(reader: &mut impl Read) -> Result<Self, SnapshotError> {
+        let mut data = vec![];
+        reader.read_to_end(&mut data)?;
+        let mut reader = data.as_slice();
+
+        let magic = take(&mut reader, MAGIC.len())?;
+        if magic != MAGIC {
+            return Err(SnapshotError::Format("not a Lenia snapshot".to_string()));
+        }
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            return Err(SnapshotError::Format(format!(
+                "unsupported version {version}"
+            )));
+        }
+
+        let length = read_u32(&mut reader)? as usize;
+        let scenario = std::str::from_utf8(take(&mut reader, length)?)
+            .map_err(|_| SnapshotError::Format("scenario is not UTF-8".to_string()))?;
+        let mut scenario = Scenario::from_ron(scenario)?;
+
+        let step = u64::from_le_bytes(take(&mut reader, 8)?.try_into().unwrap());
+        let (width, height, channels) = (
+            read_u32(&mut reader)?,
+            read_u32(&mut reader)?,
+            read_u32(&mut reader)?,
+        );
+        if channels != scenario.channels {
+            return Err(SnapshotError::Format(format!(
+                "{channels} channels stored for a {} channel rule",
+                scenario.channels
+            )));
+        }
+
+        let channel_bytes = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|cells| cells.checked_mul(4))
+            .ok_or_else(|| SnapshotError::Format(format!("world of {width}x{height} cells")))?;
+        let channels = (0..channels)
+            .map(|_| {
+                Ok(take(&mut reader, channel_bytes)?
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect())
+            })
+            .collect::<Result<_, SnapshotError>>()?;
+
+        scenario.world_size = (width, height);
+        Ok(Self {
+            scenario,
+            step,
+            channels,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::read(&mut io::BufReader::new(fs::File::open(path)?))
+    }
+}
+
+/// The next `length` bytes, without reading past the end of the snapshot.
+fn take<'a>(reader: &mut &'a [u8], length: usize) -> Result<&'a [u8], SnapshotError> {
+    if length > reader.len() {
+        return Err(SnapshotError::Format(format!(
+            "{length} bytes expected, {} left",
+            reader.len()
+        )));
+    }
+    let (bytes, rest) = reader.split_at(length);
+    *reader = rest;
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, SnapshotError> {
+    Ok(u32::from_le_bytes(take(reader, 4)?.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        let scenario = Scenario::from_toml(
+            r#"
+            world_size = [6, 5]
+            radius = 2
+            dt = 0.1
+            growth_resolution = 100
+            seed = 3
+
+            [[kernels]]
+            beta = [1.0]
+            core = { type = "GaussianCore", alpha = 4.0 }
+            growth = { type = "GaussianGrowth", mu = 0.15, sigma = 0.015 }
+            "#,
+        )
+        .unwrap();
+        let cells = (0..30).map(|index| index as f32 / 29.0).collect();
+        Snapshot::new(scenario, 42, vec![cells]).unwrap()
+    }
+
+    fn bytes(snapshot: &Snapshot) -> Vec<u8> {
+        let mut bytes = vec![];
+        snapshot.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Offset of the width, right after the scenario and the step.
+    fn size_offset(bytes: &[u8]) -> usize {
+        let length = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        16 + length + 8
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let snapshot = snapshot();
+        assert_eq!(
+            Snapshot::read(&mut bytes(&snapshot).as_slice()),
+            Ok(snapshot)
+        );
+    }
+
+    #[test]
+    fn rejects_channels_not_matching_the_scenario() {
+        let Snapshot { scenario, .. } = snapshot();
+        assert!(Snapshot::new(scenario.clone(), 0, vec![vec![0.0; 29]]).is_err());
+        assert!(Snapshot::new(scenario, 0, vec![vec![0.0; 30]; 2]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_files() {
+        let bytes = bytes(&snapshot());
+        assert!(Snapshot::read(&mut &bytes[..bytes.len() - 1]).is_err());
+        assert!(Snapshot::read(&mut &b"LENIASNQ"[..]).is_err());
+
+        // a scenario longer than the file
+        let mut corrupt = bytes.clone();
+        corrupt[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Snapshot::read(&mut corrupt.as_slice()).is_err());
+
+        // a world whose size overflows u32
+        let mut corrupt = bytes.clone();
+        let offset = size_offset(&bytes);
+        corrupt[offset..offset + 8].fill(0xff);
+        assert!(Snapshot::read(&mut corrupt.as_slice()).is_err());
+    }
+
+    #[test]
+    fn continues_on_the_cpu() {
+        let snapshot = snapshot();
+        let cpu_lenia = snapshot.to_cpu().unwrap();
+        let restored = Snapshot::from_cpu(snapshot.scenario.clone(), 42, &cpu_lenia).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+}
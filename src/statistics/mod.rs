@@ -0,0 +1,128 @@
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+/// Cells above this value count as occupied.
+pub const OCCUPIED_THRESHOLD: f32 = 0.1;
+
+/// Summary of one channel of a world, to follow long runs without watching them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStatistics {
+    pub channel: u32,
+    pub mass: f32, // sum of the cells
+    pub mean: f32,
+    pub max: f32,
+    pub occupied: f32, // fraction of cells above `OCCUPIED_THRESHOLD`
+    pub centroid: Option<(f32, f32)>, // center of mass in cells, `None` for an empty channel
+}
+
+impl ChannelStatistics {
+    /// Measures row-major `cells` of a world of `space_resolution`. The centroid is a circular
+    /// mean along both axes, so creatures crossing the wrapping edges keep a sensible center.
+    pub fn measure(channel: u32, cells: &[f32], space_resolution: (u32, u32)) -> Self {
+        let (width, height) = space_resolution;
+        let (mut mass, mut max, mut occupied) = (0.0, 0.0_f32, 0);
+        // mass weighted unit vectors of the x and y angles around the torus
+        let (mut x_sum, mut y_sum) = ((0.0, 0.0), (0.0, 0.0));
+        for (index, cell) in cells.iter().enumerate() {
+            let x_angle = (index as u32 % width) as f32 / width as f32 * TAU;
+            let y_angle = (index as u32 / width) as f32 / height as f32 * TAU;
+            mass += cell;
+            max = max.max(*cell);
+            occupied += (*cell > OCCUPIED_THRESHOLD) as usize;
+            x_sum = (
+                x_sum.0 + cell * x_angle.cos(),
+                x_sum.1 + cell * x_angle.sin(),
+            );
+            y_sum = (
+                y_sum.0 + cell * y_angle.cos(),
+                y_sum.1 + cell * y_angle.sin(),
+            );
+        }
+
+        let position = |(cos, sin): (f32, f32), size: u32| {
+            f32::atan2(sin, cos).rem_euclid(TAU) / TAU * size as f32
+        };
+        Self {
+            channel,
+            mass,
+            mean: mass / cells.len().max(1) as f32,
+            max,
+            occupied: occupied as f32 / cells.len().max(1) as f32,
+            centroid: (mass > 0.0).then(|| (position(x_sum, width), position(y_sum, height))),
+        }
+    }
+
+    /// Measures every channel of a world.
+    pub fn measure_world(channels: &[Vec<f32>], space_resolution: (u32, u32)) -> Vec<Self> {
+        channels
+            .iter()
+            .enumerate()
+            .map(|(channel, cells)| Self::measure(channel as u32, cells, space_resolution))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: (u32, u32) = (16, 8);
+
+    fn world(live: &[((u32, u32), f32)]) -> Vec<f32> {
+        let mut cells = vec![0.0; (SIZE.0 * SIZE.1) as usize];
+        for ((x, y), value) in live {
+            cells[(y * SIZE.0 + x) as usize] = *value;
+        }
+        cells
+    }
+
+    fn assert_near(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} is not {expected:?}"
+        );
+    }
+
+    #[test]
+    fn empty_channels_have_no_centroid() {
+        let statistics = ChannelStatistics::measure(0, &world(&[]), SIZE);
+        assert_eq!(
+            (statistics.mass, statistics.max, statistics.occupied),
+            (0.0, 0.0, 0.0)
+        );
+        assert_eq!(statistics.centroid, None);
+    }
+
+    #[test]
+    fn measures_mass_and_occupancy() {
+        let cells = world(&[((3, 5), 1.0), ((4, 5), 0.5), ((10, 1), 0.05)]);
+        let statistics = ChannelStatistics::measure(2, &cells, SIZE);
+        assert_eq!(statistics.channel, 2);
+        assert!((statistics.mass - 1.55).abs() < 1e-6);
+        assert!((statistics.mean - 1.55 / 128.0).abs() < 1e-6);
+        assert_eq!(statistics.max, 1.0);
+        // the faint cell is below `OCCUPIED_THRESHOLD`
+        assert_eq!(statistics.occupied, 2.0 / 128.0);
+    }
+
+    #[test]
+    fn centroids_follow_creatures_across_the_edges() {
+        let single = ChannelStatistics::measure(0, &world(&[((3, 5), 1.0)]), SIZE);
+        assert_near(single.centroid.unwrap(), (3.0, 5.0));
+
+        // split over the left and right edge, and the top and bottom one
+        let cells = world(&[((0, 0), 1.0), ((15, 0), 1.0), ((0, 7), 1.0), ((15, 7), 1.0)]);
+        let wrapped = ChannelStatistics::measure(0, &cells, SIZE);
+        assert_near(wrapped.centroid.unwrap(), (15.5, 7.5));
+    }
+
+    #[test]
+    fn measures_every_channel() {
+        let channels = vec![world(&[((1, 1), 1.0)]), world(&[])];
+        let statistics = ChannelStatistics::measure_world(&channels, SIZE);
+        assert_eq!(statistics.len(), 2);
+        assert_eq!((statistics[0].channel, statistics[1].channel), (0, 1));
+        assert_eq!(statistics[1].centroid, None);
+    }
+}
@@ -0,0 +1,91 @@
+//! Runs a scenario on the GPU without a window, reads the world back after `STEPS` updates and
+//! compares it with the CPU backend run for the same number of steps from the same state.
+//!
+//! Needs a GPU adapter, run with `cargo test --test gpu_reference -- --ignored`.
+
+use bevy::{
+    render::pipelined_rendering::PipelinedRenderingPlugin, window::ExitCondition,
+    winit::WinitPlugin,
+};
+use lenia::*;
+
+// rounding differences between the backends grow every step, see the `cpu_backend` tests
+const STEPS: u64 = 20;
+const TOLERANCE: f32 = 1e-3;
+const MAX_FRAMES: usize = 1000;
+
+#[derive(Resource, Default)]
+struct Requested(bool);
+
+fn request_snapshot(
+    mut requested: ResMut<Requested>,
+    clock: Res<SimulationClock>,
+    mut take: EventWriter<TakeSnapshot>,
+) {
+    if !requested.0 && clock.get_step() >= STEPS {
+        take.send(TakeSnapshot { path: None });
+        requested.0 = true;
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn gpu_matches_cpu_reference() {
+    let scenario = Scenario::load("assets/scenarios/orbium.lenia.toml").unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>()
+            // updated by hand below, the render world runs inline
+            .disable::<PipelinedRenderingPlugin>(),
+    )
+    .add_plugin(LeniaRenderPlugin::new(scenario.to_board().unwrap()))
+    .add_plugin(LeniaComputePlugin)
+    .init_resource::<Requested>()
+    .add_system(request_snapshot);
+    assert!(
+        app.world.contains_resource::<SimulationClock>(),
+        "the GPU pipeline could not be set up"
+    );
+
+    let mut reader = app.world.resource::<Events<SnapshotTaken>>().get_reader();
+    let snapshot = (0..MAX_FRAMES)
+        .find_map(|_| {
+            app.update();
+            let events = app.world.resource::<Events<SnapshotTaken>>();
+            reader
+                .iter(events)
+                .last()
+                .map(|SnapshotTaken(snapshot)| snapshot.clone())
+        })
+        .expect("no snapshot was read back");
+
+    let mut cpu_lenia =
+        CpuLenia::new(&scenario.to_board().unwrap()).with_convolution_mode(ConvolutionMode::Direct);
+    for _ in 0..snapshot.step {
+        cpu_lenia.step();
+    }
+
+    let max_difference = snapshot
+        .channels
+        .iter()
+        .enumerate()
+        .flat_map(|(channel, cells)| {
+            cells
+                .iter()
+                .zip(cpu_lenia.cells(channel as u32))
+                .map(|(gpu, cpu)| (gpu - cpu).abs())
+        })
+        .fold(0.0, f32::max);
+    assert!(
+        max_difference <= TOLERANCE,
+        "step {}: GPU and CPU cells differ by {max_difference}",
+        snapshot.step
+    );
+}